@@ -15,14 +15,21 @@
 	along with file_hasher.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+mod backups;
+mod binary_format;
 pub mod e_d_element;
 pub mod errors;
+mod ignore_files;
+mod list_diff;
+mod list_format;
+mod manifest;
+mod reports;
+mod sync_plan;
 
 use std::{
-	collections::HashMap,
-	convert::TryFrom,
+	collections::{HashMap, HashSet},
 	fs::{canonicalize, create_dir_all, File},
-	io::{BufRead, BufReader, Write},
+	io::{BufReader, Write},
 	path::Path,
 };
 
@@ -35,43 +42,32 @@ use errors::*;
 use join::try_join;
 use rayon::prelude::*;
 
-use self::e_d_element::EDElement;
+pub use self::{
+	backups::RetentionPolicy,
+	list_diff::ListDifference,
+	list_format::{ListCompression, ListFormat, ListVersion, MigrationReport},
+	reports::{BannedGroup, DeletionCandidate, DeletionReason, DuplicateGroup},
+	sync_plan::{FileOperation, SyncPlan},
+};
+use self::{
+	e_d_element::{
+		errors::{EDElementError, EDElementVerifyError},
+		EDElement,
+		EDVariantFields,
+		ModifiedTime,
+	},
+	ignore_files::{AcceptedIgnoreFiles, IgnoreFile},
+	manifest::ManifestEntry,
+};
 use super::{
 	path_banlist::PathBanlist,
 	shared,
-	shared::{constants::*, Checksum, SlashEnding, StubUserInterface, UserInterface, YesNo, YesNoAuto},
+	shared::{
+		constants::*, Checksum, FileChecksum, HashAlgorithm, ProgressEvent, ProgressPhase, StubUserInterface, UserInterface, YesNo,
+		YesNoAuto,
+	},
 };
 
-enum ListVersion<'a> {
-	V1_0,
-	V1_1,
-	MissingIdentifier,
-	InvalidVersion(&'a str),
-}
-
-#[derive(Debug)]
-enum FileOperation {
-	Delete(String),
-	Move { from: String, to: String },
-	Copy { from: String, to: String },
-}
-impl std::fmt::Display for FileOperation {
-	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-		use FileOperation::*;
-		let from_convert = |from| {
-			canonicalize(&from)
-				.map(|from| from.to_str().unwrap().to_string())
-				.unwrap_or(format!("'''Error getting canonical path of {}'''", from))
-		};
-
-		match self {
-			Delete(path) => write!(f, "Delete {}", path),
-			Move { from, to } => write!(f, "Move {} to {}", from_convert(from), to),
-			Copy { from, to } => write!(f, "Copy {} to {}", from_convert(from), to),
-		}
-	}
-}
-
 /// EDList is a list of all the files in a subdirectory
 /// to the current directory, excepting the files that
 /// lies under the paths that exists in the banlist.
@@ -89,12 +85,36 @@ impl std::fmt::Display for FileOperation {
 /// file. Also the saved checksum is used in the memory,
 /// such that it is very hard for a memory error to cause
 /// data corruption in the file after a reload.
+///
+/// The loaded_version is the list version that the file_hashes
+/// file was written in, the list is always written in the current version.
+///
+/// The hash_algorithm is used for hashing every file in the list,
+/// it is stored in the file_hashes file.
+///
+/// The accepted_ignore_files are the paths and the combined checksum of the
+/// .file_hasher_ignore files, that were used when indexing the files,
+/// they are stored in the file_hashes file, so that changes to the
+/// ignore files can be detected, see accept_ignore_files.
+/// The ignore_files are the accepted ignore files, read when the list was opened.
+///
+/// The format and the compression are used when writing the list and its backups,
+/// they are the ones the list was read in, unless changed using set_format or set_compression.
+///
+/// The root_path is None for lists opened using open_read_only,
+/// such lists can't be written.
 #[derive(Debug, Clone)]
 pub struct EDList {
-	element_list: Vec<EDElement>,
-	banlist:      PathBanlist,
-	xor_checksum: Checksum,
-	root_path:    String,
+	element_list:          Vec<EDElement>,
+	banlist:               PathBanlist,
+	xor_checksum:          Checksum,
+	root_path:             Option<String>,
+	loaded_version:        ListVersion,
+	hash_algorithm:        HashAlgorithm,
+	accepted_ignore_files: AcceptedIgnoreFiles,
+	ignore_files:          Vec<IgnoreFile>,
+	format:                ListFormat,
+	compression:           ListCompression,
 }
 impl EDList {
 	/// Attempts to open the {root_path}/file_hasher_files/file_hashes file
@@ -102,11 +122,42 @@ impl EDList {
 	///
 	/// If it is unable to open the file, it may ask the user
 	/// whether it should create a new file, using an object implementing
-	/// UserInterface.
+	/// UserInterface. A new list uses the default HashAlgorithm,
+	/// which can be changed using set_hash_algorithm.
+	///
+	/// If file_hashes is corrupt, it may offer to restore
+	/// the newest valid backup, see recover_from_backups.
+	///
+	/// The accepted .file_hasher_ignore files are read, and must not have changed since
+	/// file_hashes was written, new ignore files are only detected when the directory is indexed,
+	/// see accept_ignore_files. A new list uses the ignore files that exist when it is created.
 	///
 	/// Also writes a backup of the file_hashes file,
 	/// to the file_hash_backups folder, when file_hashes has been read.
 	pub fn open(root_path: &str, user_interface: &impl UserInterface, banlist: PathBanlist) -> Result<EDList, EDListOpenError> {
+		let mut e_d_list = EDList::open_without_ignore_files(root_path, user_interface, banlist)?;
+		e_d_list.ignore_files = e_d_list.accepted_ignore_files.read()?;
+		e_d_list.write_backup(root_path)?;
+		Ok(e_d_list)
+	}
+
+	/// Opens the list like open, but searches the directory for the .file_hasher_ignore files,
+	/// and asks the user whether they should be accepted if they have changed, instead of returning an error.
+	/// The accepted ignore files are used, when the list is written.
+	pub fn open_and_accept_ignore_files(
+		root_path: &str, user_interface: &impl UserInterface, banlist: PathBanlist,
+	) -> Result<EDList, EDListOpenError> {
+		let mut e_d_list = EDList::open_without_ignore_files(root_path, user_interface, banlist)?;
+		e_d_list.accept_ignore_files(user_interface)?;
+		e_d_list.write_backup(root_path)?;
+		Ok(e_d_list)
+	}
+
+	/// Used internally by open and open_and_accept_ignore_files,
+	/// to read or create the list without reading the accepted ignore files, or writing a backup.
+	fn open_without_ignore_files(
+		root_path: &str, user_interface: &impl UserInterface, banlist: PathBanlist,
+	) -> Result<EDList, EDListOpenError> {
 		let file = match File::open(format!("{}/file_hasher_files/file_hashes", root_path)) {
 			Ok(file) => file,
 			Err(err) => {
@@ -122,9 +173,12 @@ impl EDList {
 						// Even if the program should run successfully after making such a jump, it will
 						// write an invalid xor_checksum to the hash_file, which will create an error the
 						// next time the file is opened.
-						Box::new(EDList::new(root_path.to_string(), banlist, Vec::new(), Checksum::default()))
+						Box::new(EDList::new(Some(root_path.to_string()), banlist, list_format::ReadList::empty()))
 					}
-					return Ok(*create_empty_e_d_list(user_interface, root_path, banlist));
+					let mut e_d_list = *create_empty_e_d_list(user_interface, root_path, banlist);
+					e_d_list.ignore_files = e_d_list.find_ignore_files(user_interface)?;
+					e_d_list.accepted_ignore_files = AcceptedIgnoreFiles::from_ignore_files(&e_d_list.ignore_files);
+					return Ok(e_d_list);
 				}
 				else {
 					return Err(EDListOpenError::CouldNotOpenFileHashesFile);
@@ -132,118 +186,283 @@ impl EDList {
 			},
 		};
 
-		let mut lines = BufReader::new(file).lines().collect::<Result<Vec<_>, _>>()?.into_iter();
-
-		let (version_line, xor_checksum_line, fin_checksum_line) =
-			try_join!(lines.next(), lines.next(), lines.next()).ok_or(EDListOpenError::ChecksumsMissingError)?;
+		let read_list = match list_format::read_list(BufReader::new(file)) {
+			Ok(read_list) => read_list,
+			Err(err) if err.is_corruption() => match EDList::recover_from_backups(root_path, &err, user_interface)? {
+				Some(read_list) => read_list,
+				None => return Err(err),
+			},
+			Err(err) => return Err(err),
+		};
+		Ok(EDList::new(Some(root_path.to_string()), banlist, read_list))
+	}
 
-		// Handling list version.
-		match EDList::get_version_from_line(version_line.as_ref()) {
-			ListVersion::V1_1 => (),
-			ListVersion::V1_0 => Err(UnsupportedEDListVersion::V1_0)?,
-			ListVersion::MissingIdentifier => Err(UnsupportedEDListVersion::MissingIdentifier)?,
-			ListVersion::InvalidVersion(version_identifier) => Err(UnsupportedEDListVersion::Invalid(version_identifier.to_owned()))?,
+	/// Searches hash_file_backups for the newest backup whose checksums are valid,
+	/// after err showed that file_hashes is corrupt.
+	///
+	/// The lines that differ between the corrupt file_hashes and the backup
+	/// are shown, before asking the user whether to restore the backup.
+	/// The corrupt file_hashes is kept as file_hashes.corrupt-{timestamp}.
+	///
+	/// Returns None if no valid backup was found, or the user declined to restore it.
+	fn recover_from_backups(
+		root_path: &str, err: &EDListOpenError, user_interface: &impl UserInterface,
+	) -> Result<Option<list_format::ReadList>, EDListOpenError> {
+		let answer: YesNo = user_interface.get_user_answer(&format!(
+			"file_hashes is corrupt, err = {}\nDo you wish to search hash_file_backups for a valid backup?",
+			err
+		));
+		if answer == YesNo::No {
+			return Ok(None);
 		}
 
-		// Parsing file_xor_checksum
-		let file_xor_checksum = if let Some(xor_checksum_string) = xor_checksum_line.strip_prefix(XOR_CHECKSUM_PREFIX) {
-			let mut xor_checksum = Checksum::default();
-			hex::decode_to_slice(xor_checksum_string, &mut *xor_checksum)?;
-			xor_checksum
-		}
-		else {
-			Err(EDListOpenError::InvalidXorChecksum)?
+		let backup_dir = format!("{}/file_hasher_files/hash_file_backups", root_path);
+		let backup_list = match backups::list_backups(Path::new(&backup_dir)) {
+			Ok(backup_list) => backup_list,
+			Err(err) => {
+				user_interface.send_message(&format!("Could not read the backups in {}, err = {}", backup_dir, err));
+				return Ok(None);
+			},
 		};
 
-		// Parsing file_final_checksum
-		let file_final_checksum = fin_checksum_line.strip_prefix(FIN_CHECKSUM_PREFIX).ok_or(EDListOpenError::InvalidFinChecksum)?;
-		let mut xor_checksum = Checksum::default();
-		let mut hasher = Blake2bVar::new(HASH_OUTPUT_LENGTH).unwrap();
+		for backup in backup_list {
+			let contents = match std::fs::read(&backup.path) {
+				Ok(contents) => contents,
+				Err(err) => {
+					user_interface.send_message(&format!("Could not read backup {}, err = {}", backup.name, err));
+					continue;
+				},
+			};
+			let read_list = match list_format::read_list(contents.as_slice()) {
+				Ok(read_list) => read_list,
+				Err(err) => {
+					user_interface.send_message(&format!("Backup {} is not valid, err = {}", backup.name, err));
+					continue;
+				},
+			};
 
-		// Parsing all EDElements.
-		let e_d_elements = lines
-			.collect::<Vec<_>>()
-			.into_par_iter()
-			.enumerate()
-			.map(|(i, line)| EDElement::try_from(line.as_ref()).map_err(|err| (err, i)))
-			.collect::<Result<Vec<_>, _>>()?;
-
-		// Processing the checksums, so that we can verify the integrity
-		// of the file before returning.
-		e_d_elements.iter().for_each(|element| {
-			hasher.update(element.get_hash().as_ref());
-			xor_checksum ^= element.get_hash();
-		});
-		hasher.update(file_xor_checksum.as_ref());
-		let final_checksum = shared::blake2_to_checksum(hasher);
+			let hash_file_path = format!("{}/file_hasher_files/file_hashes", root_path);
+			let corrupt_contents = std::fs::read(&hash_file_path)?;
+			user_interface.send_message(&format!(
+				"The newest valid backup is {}, it contains {} elements.\n{}",
+				backup.name,
+				read_list.element_list.len(),
+				shared::describe_differences(
+					&list_format::decode_list_lossy(&corrupt_contents),
+					"the corrupt file_hashes",
+					&list_format::decode_list_lossy(&contents),
+					"the backup"
+				)
+			));
+
+			let corrupt_path = format!(
+				"{}/file_hasher_files/file_hashes.corrupt-{}",
+				root_path,
+				Local::now().format(BACKUP_TIMESTAMP_FORMAT)
+			);
+			let answer: YesNo = user_interface.get_user_answer(&format!(
+				"Do you wish to restore file_hashes from this backup?\nThe corrupt file_hashes will be kept as {}",
+				corrupt_path
+			));
+			if answer == YesNo::No {
+				return Ok(None);
+			}
+			shared::write_file_atomically(Path::new(&corrupt_path), &corrupt_contents).map_err(EDListOpenError::RestoreBackupError)?;
+			shared::write_file_atomically(Path::new(&hash_file_path), &contents).map_err(EDListOpenError::RestoreBackupError)?;
+			user_interface.send_message(&format!("Restored file_hashes from backup {}", backup.name));
+			return Ok(Some(read_list));
+		}
+
+		user_interface.send_message("No valid backup was found in hash_file_backups");
+		Ok(None)
+	}
 
-		// By creating the EDList object before comparing xor_checksum with
-		// the one saved in the file_hashes file, we hopefully avoid any optimizations
-		// that would prevent the edlist from using the generated xorchecksum, after comparison.
-		let e_d_list = EDList::new(root_path.to_string(), banlist, e_d_elements, file_xor_checksum);
+	/// Reads the list at list_path, without a banlist, and without writing a backup.
+	/// This makes it possible to verify a directory on read-only media,
+	/// using a list that is stored outside of the directory.
+	///
+	/// Like for every other list, the paths of the elements are relative
+	/// to the current directory. The returned list can't be written.
+	pub fn open_read_only(list_path: &str) -> Result<EDList, EDListOpenError> {
+		let file = File::open(list_path)?;
+		let read_list = list_format::read_list(BufReader::new(file))?;
+		Ok(EDList::new(None, PathBanlist::new_dummy(), read_list))
+	}
 
-		// Verifying xor_checksum
-		if e_d_list.xor_checksum != xor_checksum {
-			Err(EDListOpenError::XorChecksumMismatch)?
+	/// Creates a new EDList, containing the elements of read_list.
+	fn new(root_path: Option<String>, banlist: PathBanlist, read_list: list_format::ReadList) -> EDList {
+		EDList {
+			element_list: read_list.element_list,
+			banlist,
+			xor_checksum: read_list.xor_checksum,
+			root_path,
+			loaded_version: read_list.version,
+			hash_algorithm: read_list.hash_algorithm,
+			accepted_ignore_files: read_list.accepted_ignore_files,
+			ignore_files: Vec::new(),
+			format: read_list.format,
+			compression: read_list.compression,
 		}
+	}
+
+	pub fn get_hash_algorithm(&self) -> HashAlgorithm {
+		self.hash_algorithm
+	}
 
-		// Verifying final_checksum.
-		if file_final_checksum != final_checksum.to_string() {
-			Err(EDListOpenError::FinChecksumMismatch)?
+	/// Changes the hash algorithm used for hashing files.
+	/// Since every file in a list must be hashed using the same algorithm,
+	/// this is only possible while the list is empty, or if the
+	/// algorithm is already used by the list.
+	pub fn set_hash_algorithm(&mut self, hash_algorithm: HashAlgorithm) -> Result<(), SetHashAlgorithmError> {
+		if hash_algorithm != self.hash_algorithm && !self.element_list.is_empty() {
+			return Err(SetHashAlgorithmError::ListNotEmpty(self.hash_algorithm));
 		}
+		self.hash_algorithm = hash_algorithm;
+		Ok(())
+	}
+
+	pub fn get_format(&self) -> ListFormat {
+		self.format
+	}
 
-		e_d_list.write_backup()?;
+	/// Changes the format that the list and its backups are written in,
+	/// lists are read in any format.
+	pub fn set_format(&mut self, format: ListFormat) {
+		self.format = format;
+	}
 
-		Ok(e_d_list)
+	pub fn get_compression(&self) -> ListCompression {
+		self.compression
 	}
 
-	/// Creates a new empty EDList.
-	fn new(root_path: String, banlist: PathBanlist, element_list: Vec<EDElement>, xor_checksum: Checksum) -> EDList {
-		EDList { element_list, banlist, xor_checksum, root_path }
+	/// Changes the compression that the list and its backups are written in,
+	/// lists are read in any compression.
+	pub fn set_compression(&mut self, compression: ListCompression) {
+		self.compression = compression;
+	}
+
+	/// Converts the elements that were created by earlier list versions,
+	/// by storing the file sizes and the modified times in nanoseconds,
+	/// that the earlier versions didn't store.
+	/// They are only stored for elements whose modified time in whole seconds
+	/// is unchanged, such that a modified file isn't accepted.
+	/// The list is written in the current list version the next time it is written.
+	///
+	/// If dry_run is true, the elements are only examined, and the list isn't changed.
+	/// Returns a report of what was, or would be, converted.
+	pub fn migrate(&mut self, dry_run: bool) -> MigrationReport {
+		let mut report = MigrationReport {
+			from_version: self.loaded_version,
+			to_version: ListVersion::current(),
+			hash_algorithm: self.hash_algorithm,
+			element_count: self.element_list.len(),
+			sizes_stored: 0,
+			nanos_stored: 0,
+			files_without_size: 0,
+			elements_without_nanos: 0,
+			dry_run,
+		};
+		for index in 0..self.element_list.len() {
+			let element = &self.element_list[index];
+			let missing_size = usize::from(matches!(element.get_variant(), EDVariantFields::File { checksum: _, size: None }));
+			let missing_nanos = usize::from(element.get_modified_time().get_nanos().is_none());
+			if missing_size == 0 && missing_nanos == 0 {
+				continue;
+			}
+			let converted = std::fs::symlink_metadata(element.get_path())
+				.ok()
+				.and_then(|metadata| element.with_missing_metadata(&metadata));
+			match converted {
+				Some(converted) => {
+					report.sizes_stored += missing_size;
+					report.nanos_stored += missing_nanos;
+					if !dry_run {
+						self.replace_e_d_element(index, converted);
+					}
+				},
+				None => {
+					report.files_without_size += missing_size;
+					report.elements_without_nanos += missing_nanos;
+				},
+			}
+		}
+		report
 	}
 
 	/// Tests every element in the lists integrity against
 	/// the real files and links, they refer to.
 	/// Returns a vector with strings describing all the errors.
 	/// Also sends a message to the UserInterface impl, for every
-	/// element that is being tested.
-	pub fn verify(&self, prefix: Option<&str>, user_interface: &impl UserInterface) -> Vec<VerifyError> {
+	/// element that has been tested.
+	///
+	/// The elements are tested using the given amount of threads,
+	/// if threads is 0, a thread is used for every logical cpu.
+	pub fn verify(&self, prefix: Option<&str>, threads: usize, user_interface: &impl UserInterface) -> Vec<VerifyError> {
 		if let Some(prefix) = prefix {
 			let prefix_elements: Vec<_> = self.element_list.iter().filter(|e| e.get_path().strip_prefix(prefix).is_some()).collect();
-			self.verify_loop(&prefix_elements, user_interface)
+			self.verify_loop(&prefix_elements, threads, user_interface)
 		}
 		else {
-			self.verify_loop(&self.element_list, user_interface)
+			self.verify_loop(&self.element_list, threads, user_interface)
 		}
 	}
 
 	/// Verify all symbolic links in the EDList.
-	pub fn verify_links(&self, user_interface: &impl UserInterface) -> Vec<VerifyError> {
+	pub fn verify_links(&self, threads: usize, user_interface: &impl UserInterface) -> Vec<VerifyError> {
 		let link_elements: Vec<_> = self.element_list.iter().filter(|e| e.get_variant().is_link()).collect();
-		self.verify_loop(&link_elements, user_interface)
+		self.verify_loop(&link_elements, threads, user_interface)
 	}
 
 	/// Goes through all the elements in the given element_list.
 	/// It returns a list of all the errors in a string format.
-	fn verify_loop<T: AsRef<EDElement>>(&self, element_list: &[T], user_interface: &impl UserInterface) -> Vec<VerifyError> {
-		let mut error_list = Vec::new();
+	///
+	/// The errors are always returned in the order of element_list,
+	/// no matter the amount of threads used for the testing.
+	fn verify_loop<T: AsRef<EDElement> + Sync>(
+		&self, element_list: &[T], threads: usize, user_interface: &impl UserInterface,
+	) -> Vec<VerifyError> {
 		let list_length = element_list.len();
 		let list_length_width = list_length.to_string().chars().count();
+		let mut results: Vec<_> = element_list.iter().map(|_| None).collect();
+		let mut file_count = 0;
+		let mut bytes_processed = 0;
+
+		let pool = shared::build_thread_pool(threads);
+		shared::run_in_thread_pool(
+			&pool,
+			element_list.iter().enumerate().collect(),
+			|(index, e_d_element)| (index, e_d_element.as_ref().test_integrity(self.hash_algorithm)),
+			|(index, result)| {
+				let e_d_element = element_list[index].as_ref();
+				file_count += 1;
+				bytes_processed += e_d_element.get_variant().size();
+				if !user_interface.shows_progress() {
+					user_interface.send_message(&format!(
+						"Verified file {:0width$} of {} = {}",
+						file_count,
+						list_length,
+						e_d_element.get_path(),
+						width = list_length_width
+					));
+				}
+				user_interface.send_progress(&ProgressEvent {
+					phase: ProgressPhase::Verifying,
+					current: file_count,
+					total: list_length,
+					bytes_processed,
+					path: e_d_element.get_path(),
+				});
+				results[index] = Some(result);
+			},
+		);
 
-		for (file_count, e_d_element) in element_list.iter().enumerate() {
+		let mut error_list = Vec::new();
+		for (e_d_element, result) in element_list.iter().zip(results) {
 			let path = e_d_element.as_ref().get_path();
-			user_interface.send_message(&format!(
-				"Verifying file {:0width$} of {} = {}",
-				file_count + 1,
-				list_length,
-				path,
-				width = list_length_width
-			));
-
-			if let Err(err) = e_d_element.as_ref().test_integrity() {
+			if let Some(Err(err)) = result {
 				error_list.push(err.into());
 			}
-			if self.banlist.is_in_banlist(path) {
+			if self.is_excluded(path) {
 				error_list.push(VerifyError::PathInBanlist(path.to_string()));
 			}
 		}
@@ -252,75 +471,356 @@ impl EDList {
 
 	/// Finds all the paths that are deleted, or modified
 	/// and removes them from the list, if the user agrees.
-	/// Also removes files that has a prefix in the banlist.
-	/// If the file has a prefix in the banlist, we do not test
-	/// its metadata.
+	/// Also removes files that are excluded by the banlist or the ignore files.
+	/// If the file is excluded, we do not test its metadata.
 	pub fn delete(&mut self, user_interface: &impl UserInterface) {
-		let old_list_len = self.element_list.len();
-		let old_list = std::mem::replace(&mut self.element_list, Vec::with_capacity(old_list_len));
-		let new_list = &mut self.element_list;
-
 		let mut auto_action: Option<YesNo> = None;
-		let mut deleted_paths: Vec<String> = Vec::new();
+		let paths_to_delete: Vec<String> = self
+			.find_deletion_candidates(user_interface)
+			.into_iter()
+			.filter(|candidate| {
+				let question = format!("{}\nDo you wish to delete this path?", candidate);
+				EDList::ask_yes_no_auto(&question, &mut auto_action, user_interface) == YesNo::Yes
+			})
+			.map(|candidate| candidate.get_path().to_string())
+			.collect();
+		let deleted_paths = self.delete_paths(&paths_to_delete);
+
+		if !deleted_paths.is_empty() {
+			let deleted_paths_length = deleted_paths.len();
+			let length_width = deleted_paths_length.to_string().chars().count();
+			user_interface.send_message(&format!("Deleted paths, amount = {}", deleted_paths_length));
+			for (index, deleted_path) in deleted_paths.iter().enumerate() {
+				user_interface.send_message(&format!(
+					"{:0width$} of {}: {}",
+					index + 1,
+					deleted_paths_length,
+					deleted_path,
+					width = length_width
+				));
+			}
+		}
+	}
+
+	/// Used to test whether a path is excluded from the list, by the banlist or by
+	/// the accepted .file_hasher_ignore files, see ignore_files::is_excluded.
+	/// Paths of directories must end with a forward slash.
+	pub fn is_excluded(&self, path: &str) -> bool {
+		ignore_files::is_excluded(&self.banlist, &self.ignore_files, path)
+	}
 
-		let xor_checksum = &mut self.xor_checksum;
+	/// Returns the elements that are excluded by the banlist or the ignore files,
+	/// or whose metadata can't be verified, in the order of the list.
+	pub fn deletion_candidates(&self) -> Vec<DeletionCandidate> {
+		self.find_deletion_candidates(&StubUserInterface::new(String::new()))
+	}
 
-		let mut delete_element = |e_d_element: EDElement| {
-			*xor_checksum ^= e_d_element.get_hash();
-			deleted_paths.push(e_d_element.take_path());
-		};
+	/// Finds the deletion candidates, while sending the progress to the user_interface.
+	fn find_deletion_candidates(&self, user_interface: &impl UserInterface) -> Vec<DeletionCandidate> {
+		let list_length = self.element_list.len();
+		self.element_list
+			.iter()
+			.enumerate()
+			.filter_map(|(index, e_d_element)| {
+				user_interface.send_progress(&ProgressEvent {
+					phase:           ProgressPhase::CheckingMetadata,
+					current:         index + 1,
+					total:           list_length,
+					bytes_processed: 0,
+					path:            e_d_element.get_path(),
+				});
+				let reason = if self.is_excluded(e_d_element.get_path()) {
+					DeletionReason::InBanlist
+				}
+				else {
+					DeletionReason::MetadataError(e_d_element.test_metadata().err()?)
+				};
+				Some(DeletionCandidate::new(e_d_element.get_path().to_string(), reason))
+			})
+			.collect()
+	}
+
+	/// Returns the elements whose paths are excluded by the banlist or the ignore files,
+	/// grouped by the rule that excludes them, in the order of the rules in the banlist,
+	/// followed by the rules of the ignore files, see ignore_files::rules.
+	pub fn banned_groups(&self) -> Vec<BannedGroup> {
+		let mut paths_by_rule: HashMap<String, Vec<String>> = HashMap::new();
+		for e_d_element in &self.element_list {
+			if let Some(rule) = ignore_files::excluding_rule(&self.banlist, &self.ignore_files, e_d_element.get_path()) {
+				paths_by_rule.entry(rule).or_default().push(e_d_element.get_path().to_string());
+			}
+		}
+		ignore_files::rules(&self.banlist, &self.ignore_files)
+			.into_iter()
+			.filter_map(|rule| {
+				let paths = paths_by_rule.remove(&rule)?;
+				Some(BannedGroup::new(rule, paths))
+			})
+			.collect()
+	}
+
+	/// Shows the elements whose paths are excluded by the banlist or the ignore files, grouped by the rule that excludes them,
+	/// and deletes all of them from the list, if the user confirms it.
+	///
+	/// Returns the deleted paths in the order of the list.
+	pub fn delete_banned(&mut self, user_interface: &impl UserInterface) -> Vec<String> {
+		let banned_groups = self.banned_groups();
+		if banned_groups.is_empty() {
+			user_interface.send_message("No elements in the list are excluded by the banlist or the ignore files");
+			return Vec::new();
+		}
+
+		let mut preview = String::new();
+		for banned_group in &banned_groups {
+			preview.push_str(&format!("Banned by \"{}\", amount = {}\n", banned_group.get_rule(), banned_group.paths().len()));
+			for path in banned_group.paths() {
+				preview.push_str(&format!("\t{}\n", path));
+			}
+		}
+		user_interface.send_message(preview.trim_end());
+
+		let banned_paths: Vec<&String> = banned_groups.iter().flat_map(BannedGroup::paths).collect();
+		let answer: YesNo =
+			user_interface.get_user_answer(&format!("Do you wish to delete these {} elements from the list?", banned_paths.len()));
+		if answer == YesNo::No {
+			return Vec::new();
+		}
+		let deleted_paths = self.delete_paths(&banned_paths);
+		user_interface.send_message(&format!("Deleted paths, amount = {}", deleted_paths.len()));
+		deleted_paths
+	}
+
+	/// Deletes the elements with the given paths from the list,
+	/// paths that aren't in the list are ignored.
+	///
+	/// Returns the deleted paths in the order of the list.
+	pub fn delete_paths<T: AsRef<str>>(&mut self, paths: &[T]) -> Vec<String> {
+		let paths: HashSet<&str> = paths.iter().map(AsRef::as_ref).collect();
+		let old_list = std::mem::take(&mut self.element_list);
+		let mut deleted_paths: Vec<String> = Vec::new();
 
 		for e_d_element in old_list.into_iter() {
-			let mut error = if self.banlist.is_in_banlist(e_d_element.get_path()) {
-				Some(format!("Path {} is in the banlist", e_d_element.get_path()))
+			if paths.contains(e_d_element.get_path()) {
+				self.xor_checksum ^= e_d_element.get_hash();
+				deleted_paths.push(e_d_element.take_path());
 			}
 			else {
-				None
-			};
+				self.element_list.push(e_d_element);
+			}
+		}
+		deleted_paths
+	}
 
-			if error.is_none() {
-				if let Err(err) = e_d_element.test_metadata() {
-					error = Some(err.to_string());
-				}
+	/// Finds all the elements whose files or links have been modified,
+	/// and replaces them with rehashed elements, if the user agrees.
+	/// The old and the new checksum or link target is shown to the user,
+	/// before the user is asked.
+	///
+	/// Elements excluded by the banlist or the ignore files, and elements whose paths
+	/// can no longer be read, are not touched, they can be removed using delete.
+	///
+	/// Returns a list of all the errors created when trying to rehash files.
+	pub fn update(&mut self, user_interface: &impl UserInterface) -> Vec<EDElementError> {
+		let mut auto_action: Option<YesNo> = None;
+		let mut errors = Vec::new();
+		let mut updated_paths = Vec::new();
+
+		let list_length = self.element_list.len();
+		let modified_indexes: Vec<usize> = (0..list_length)
+			.filter(|index| {
+				let e_d_element = &self.element_list[*index];
+				user_interface.send_progress(&ProgressEvent {
+					phase:           ProgressPhase::CheckingMetadata,
+					current:         index + 1,
+					total:           list_length,
+					bytes_processed: 0,
+					path:            e_d_element.get_path(),
+				});
+				!self.is_excluded(e_d_element.get_path()) &&
+					matches!(
+						e_d_element.test_metadata(),
+						Err(EDElementError::VerifyError(
+							EDElementVerifyError::TimeChanged(_) | EDElementVerifyError::SizeChanged(_)
+						))
+					)
+			})
+			.collect();
+
+		let modified_length = modified_indexes.len();
+		let mut bytes_processed = 0;
+		for (count, index) in modified_indexes.into_iter().enumerate() {
+			let e_d_element = &self.element_list[index];
+			let new_element = EDElement::from_path(e_d_element.get_path().to_string(), self.hash_algorithm);
+			if let Ok(new_element) = &new_element {
+				bytes_processed += new_element.get_variant().size();
 			}
-			match error {
-				None => new_list.push(e_d_element),
-				Some(err) => {
-					let answer = if let Some(auto_value) = auto_action {
-						auto_value
-					}
-					else {
-						let answer: YesNoAuto = user_interface.get_user_answer(&format!("{}\nDo you wish to delete this path?", err));
-						if let YesNoAuto::Continued(auto_value) = answer {
-							auto_action = Some(auto_value);
-						}
-						answer.get_yesno_val()
-					};
-					match answer {
-						YesNo::Yes => delete_element(e_d_element),
-						YesNo::No => new_list.push(e_d_element),
-					}
+			user_interface.send_progress(&ProgressEvent {
+				phase: ProgressPhase::Hashing,
+				current: count + 1,
+				total: modified_length,
+				bytes_processed,
+				path: e_d_element.get_path(),
+			});
+			let new_element = match new_element {
+				Ok(new_element) => new_element,
+				Err(err) => {
+					errors.push(err);
+					continue;
 				},
+			};
+			let question = format!(
+				"Path {} has been modified\nold: {}\nnew: {}\nDo you wish to update this element?",
+				e_d_element.get_path(),
+				e_d_element.get_variant(),
+				new_element.get_variant()
+			);
+			if EDList::ask_yes_no_auto(&question, &mut auto_action, user_interface) == YesNo::Yes {
+				updated_paths.push(new_element.get_path().to_string());
+				self.replace_e_d_element(index, new_element);
 			}
 		}
-		let deleted_paths_length = old_list_len - new_list.len();
-		if deleted_paths.len() != deleted_paths_length {
-			panic!("Invalid amount of elements deleted.");
-		}
 
-		if !deleted_paths.is_empty() {
-			let length_width = deleted_paths_length.to_string().chars().count();
-			user_interface.send_message(&format!("Deleted paths, amount = {}", deleted_paths_length));
-			for (index, deleted_path) in deleted_paths.iter().enumerate() {
+		if !updated_paths.is_empty() {
+			let updated_paths_length = updated_paths.len();
+			let length_width = updated_paths_length.to_string().chars().count();
+			user_interface.send_message(&format!("Updated paths, amount = {}", updated_paths_length));
+			for (index, updated_path) in updated_paths.iter().enumerate() {
 				user_interface.send_message(&format!(
 					"{:0width$} of {}: {}",
 					index + 1,
-					deleted_paths_length,
-					deleted_path,
+					updated_paths_length,
+					updated_path,
 					width = length_width
 				));
 			}
 		}
+		errors
+	}
+
+	/// Finds elements whose paths no longer exist, and pairs them with
+	/// new paths that are not yet in the list, but contain the same file or link.
+	/// Candidates are found by comparing modified times, and are then
+	/// confirmed by hashing the new paths using the given amount of threads.
+	///
+	/// The detected moves are shown to the user, and if the user agrees,
+	/// the paths of the moved elements are updated, instead of the elements
+	/// being deleted and created again.
+	///
+	/// In case of an error when indexing the files, we return an error.
+	/// Otherwise a list of all the errors created when trying to hash
+	/// new paths is returned.
+	pub fn reconcile(&mut self, threads: usize, user_interface: &impl UserInterface) -> Result<Vec<EDElementError>, IndexError> {
+		let mut vanished_elements: HashMap<u64, Vec<usize>> = HashMap::new();
+		for (index, e_d_element) in self.element_list.iter().enumerate() {
+			if let Err(EDElementError::GetMetaDataError(_, err)) = e_d_element.test_metadata() {
+				if err.kind() == std::io::ErrorKind::NotFound && !self.is_excluded(e_d_element.get_path()) {
+					vanished_elements.entry(e_d_element.get_modified_time().get_secs()).or_default().push(index);
+				}
+			}
+		}
+		if vanished_elements.is_empty() {
+			user_interface.send_message("No elements have been moved");
+			return Ok(Vec::new());
+		}
+
+		let indexed_paths = self.index(".", user_interface)?;
+		let existing_paths: std::collections::HashSet<_> = self.element_list.iter().map(|e| e.get_path()).collect();
+		let candidate_paths: Vec<_> = indexed_paths
+			.into_iter()
+			.filter(|path| !existing_paths.contains(path.as_str()))
+			.filter(|path| match std::fs::symlink_metadata(path) {
+				Ok(metadata) => vanished_elements.contains_key(&ModifiedTime::from_metadata(&metadata).get_secs()),
+				Err(_) => false,
+			})
+			.collect();
+
+		let mut errors = Vec::new();
+		let mut moves = Vec::new();
+		let candidate_paths_length = candidate_paths.len();
+		let candidate_paths_length_width = candidate_paths_length.to_string().chars().count();
+		let mut file_count = 0;
+		let mut bytes_processed = 0;
+		let hash_algorithm = self.hash_algorithm;
+		let pool = shared::build_thread_pool(threads);
+		shared::run_in_thread_pool(&pool, candidate_paths, |path| EDElement::from_path(path, hash_algorithm), |result| {
+			file_count += 1;
+			let new_element = match result {
+				Ok(new_element) => new_element,
+				Err(err) => {
+					errors.push(err);
+					return;
+				},
+			};
+			bytes_processed += new_element.get_variant().size();
+			if !user_interface.shows_progress() {
+				user_interface.send_message(&format!(
+					"Hashed move candidate {:0width$} of {} = {}",
+					file_count,
+					candidate_paths_length,
+					new_element.get_path(),
+					width = candidate_paths_length_width
+				));
+			}
+			user_interface.send_progress(&ProgressEvent {
+				phase: ProgressPhase::HashingMoveCandidates,
+				current: file_count,
+				total: candidate_paths_length,
+				bytes_processed,
+				path: new_element.get_path(),
+			});
+			if let Some(indexes) = vanished_elements.get_mut(&new_element.get_modified_time().get_secs()) {
+				let element_list = &self.element_list;
+				if let Some(position) = indexes.iter().position(|index| {
+					element_list[*index].get_modified_time().matches(&new_element.get_modified_time()) &&
+						element_list[*index].get_variant().has_same_content(new_element.get_variant())
+				}) {
+					moves.push((indexes.remove(position), new_element.take_path()));
+				}
+			}
+		});
+
+		if moves.is_empty() {
+			user_interface.send_message("No elements have been moved");
+			return Ok(errors);
+		}
+		moves.sort_unstable();
+		let moves_length = moves.len();
+		let length_width = moves_length.to_string().chars().count();
+		user_interface.send_message(&format!("Detected moves, amount = {}", moves_length));
+		for (count, (index, new_path)) in moves.iter().enumerate() {
+			user_interface.send_message(&format!(
+				"{:0width$} of {}: {} -> {}",
+				count + 1,
+				moves_length,
+				self.element_list[*index].get_path(),
+				new_path,
+				width = length_width
+			));
+		}
+		if user_interface.get_user_answer::<YesNo>("Do you wish to update the paths of these elements?") == YesNo::Yes {
+			for (index, new_path) in moves {
+				let mut moved_element = self.element_list[index].clone();
+				moved_element.update_path(new_path);
+				self.replace_e_d_element(index, moved_element);
+			}
+		}
+		Ok(errors)
+	}
+
+	/// Asks the user the given yes/no question, unless the user
+	/// has previously chosen to continue with the same answer.
+	///
+	/// auto_action stores the continued answer between calls,
+	/// it should start out as None.
+	fn ask_yes_no_auto(question: &str, auto_action: &mut Option<YesNo>, user_interface: &impl UserInterface) -> YesNo {
+		if let Some(auto_value) = auto_action {
+			return *auto_value;
+		}
+		let answer: YesNoAuto = user_interface.get_user_answer(question);
+		if let YesNoAuto::Continued(auto_value) = answer {
+			*auto_action = Some(auto_value);
+		}
+		answer.get_yesno_val()
 	}
 
 	/// Finds all the files that have not been
@@ -333,35 +833,86 @@ impl EDList {
 	///
 	/// When this function returns Ok, it returns a list with
 	/// all the errors created when trying to read files.
-	pub fn create(&mut self, user_interface: &impl UserInterface) -> Result<Vec<CreateError>, CreateError> {
+	///
+	/// The files are hashed using the given amount of threads,
+	/// if threads is 0, a thread is used for every logical cpu.
+	/// If group_by_device is true, only one file is hashed at a time
+	/// on each filesystem device, so that spinning disks are read
+	/// sequentially, while different devices are still hashed in parallel.
+	pub fn create(
+		&mut self, threads: usize, group_by_device: bool, user_interface: &impl UserInterface,
+	) -> Result<Vec<CreateError>, CreateError> {
+		use itertools::Itertools;
+
+		let indexed_paths = self.index(".", user_interface)?;
 		let existing_paths: std::collections::HashSet<_> = self.element_list.iter().map(|e| e.get_path()).collect();
-		let pending_hashing: Vec<_> = self
-			.index(".", user_interface)?
-			.into_iter()
-			.filter(|string| !existing_paths.contains(string.as_str()))
-			.collect();
+		let pending_hashing: Vec<_> = indexed_paths.into_iter().filter(|string| !existing_paths.contains(string.as_str())).collect();
 
 		let mut errors: Vec<CreateError> = Vec::new();
 
 		let pending_hashing_length = pending_hashing.len();
 		let pending_hashing_length_width = pending_hashing_length.to_string().chars().count();
-		for (i, string) in pending_hashing.into_iter().enumerate() {
-			user_interface.send_message(&format!(
-				"Hashing file {:0width$} of {} = {}",
-				i + 1,
-				pending_hashing_length,
-				string,
-				width = pending_hashing_length_width
-			));
-			match EDElement::from_path(string) {
+		let groups = if group_by_device {
+			pending_hashing.into_iter().into_group_map_by(|path| EDList::get_device_id(path)).into_values().collect()
+		}
+		else {
+			pending_hashing.into_iter().map(|path| vec![path]).collect()
+		};
+
+		let mut file_count = 0;
+		let mut bytes_processed = 0;
+		let hash_algorithm = self.hash_algorithm;
+		let pool = shared::build_thread_pool(threads);
+		shared::run_groups_in_thread_pool(&pool, groups, |path| EDElement::from_path(path, hash_algorithm), |result| {
+			file_count += 1;
+			let path = match &result {
+				Ok(new_element) => {
+					bytes_processed += new_element.get_variant().size();
+					new_element.get_path()
+				},
+				Err(err) => err.path(),
+			};
+			if !user_interface.shows_progress() {
+				user_interface.send_message(&format!(
+					"Hashed file {:0width$} of {} = {}",
+					file_count,
+					pending_hashing_length,
+					path,
+					width = pending_hashing_length_width
+				));
+			}
+			user_interface.send_progress(&ProgressEvent {
+				phase: ProgressPhase::Hashing,
+				current: file_count,
+				total: pending_hashing_length,
+				bytes_processed,
+				path,
+			});
+			match result {
 				Ok(new_element) => self.add_e_d_element(new_element),
 				Err(err) => errors.push(err.into()),
 			};
-		}
+		});
 
 		Ok(errors)
 	}
 
+	/// Returns an identifier of the filesystem device that the path is stored on.
+	/// On platforms where this is unsupported, or if the metadata of the path
+	/// can't be read, all paths are given the same identifier.
+	fn get_device_id(path: &str) -> u64 {
+		#[cfg(unix)]
+		{
+			use std::os::unix::fs::MetadataExt;
+			std::fs::symlink_metadata(path).map(|metadata| metadata.dev()).unwrap_or(0)
+		}
+		#[cfg(not(unix))]
+		{
+			let _ = path;
+			0
+		}
+	}
+
 	/// Sort this EDList according to the paths of the EDElements.
 	pub fn sort(&mut self) {
 		use std::cmp::Ordering;
@@ -400,21 +951,44 @@ impl EDList {
 		});
 	}
 
-	/// Sends a list of all the links that have the same
-	/// link_target as at least one other link
-	/// to the struct implementing UserInterface.
-	///
-	/// Also sends a list of all the files that have the
-	/// same file_hash as at least one other file to the
-	/// struct implementing UserInterface.
-	/// TODO: Fix issue where relative checksum that is moved along with target, doesn't generate a duplicate.
+	/// Sends every group of duplicates in the list to the user_interface,
+	/// first the groups of links with the same target,
+	/// then the groups of files with the same checksum, see duplicate_groups.
 	pub fn find_duplicates(&self, user_interface: &impl UserInterface) {
+		let duplicate_groups = self.duplicate_groups();
+
+		user_interface.send_message("Links with same target path and origin directory:");
+		for group in &duplicate_groups {
+			if let DuplicateGroup::Links { target, paths } = group {
+				user_interface.send_message(&format!("{:4}links with target path = \"{}\":", "", target));
+				for path in paths {
+					user_interface.send_message(&format!("{:8}{}", "", path));
+				}
+			}
+		}
+		user_interface.send_message("Files with the same checksum:");
+		for group in &duplicate_groups {
+			if let DuplicateGroup::Files { checksum, paths } = group {
+				user_interface.send_message(&format!("{:4}Files with checksum = \"{}\":", "", hex::encode_upper(checksum.as_ref())));
+				for path in paths {
+					user_interface.send_message(&format!("{:8}{}", "", path));
+				}
+			}
+		}
+		user_interface.send_message(&format!("{} unique collisions found", duplicate_groups.len()));
+	}
+
+	/// Returns every group of links with the same target path,
+	/// followed by every group of files with the same checksum.
+	///
+	/// The groups of each kind are sorted by the path of their first element.
+	pub fn duplicate_groups(&self) -> Vec<DuplicateGroup> {
 		use std::collections::hash_map::Entry;
 		let mut link_dups: HashMap<&str, Vec<&EDElement>> = HashMap::with_capacity(self.element_list.len());
-		let mut file_dups: HashMap<Checksum, Vec<&EDElement>> = HashMap::with_capacity(self.element_list.len());
+		let mut file_dups: HashMap<&FileChecksum, Vec<&EDElement>> = HashMap::with_capacity(self.element_list.len());
 		for element in &self.element_list {
 			match element.get_variant() {
-				e_d_element::EDVariantFields::File { checksum } => match file_dups.entry(*checksum) {
+				e_d_element::EDVariantFields::File { checksum, size: _ } => match file_dups.entry(checksum) {
 					Entry::Occupied(entry) => entry.into_mut().push(element),
 					Entry::Vacant(entry) => {
 						entry.insert(vec![element]);
@@ -429,37 +1003,96 @@ impl EDList {
 			}
 		}
 
-		let mut collision_blocks = 0;
-		user_interface.send_message("Links with same target path and origin directory:");
-		link_dups.iter().filter(|(_, v)| v.len() > 1).for_each(|(key, vector)| {
-			collision_blocks += 1;
-			user_interface.send_message(&format!("{:4}links with target path = \"{}\":", "", key));
-			for element in vector {
-				user_interface.send_message(&format!("{:8}{}", "", element.get_path()));
-			}
-		});
-		user_interface.send_message("Files with the same checksum:");
-		file_dups.iter().filter(|(_, v)| v.len() > 1).for_each(|(hash, vector)| {
-			collision_blocks += 1;
-			user_interface.send_message(&format!("{:4}Files with checksum = \"{}\":", "", hex::encode_upper(hash.as_ref())));
-			for element in vector {
-				user_interface.send_message(&format!("{:8}{}", "", element.get_path()));
-			}
-		});
-		user_interface.send_message(&format!("{} unique collisions found", collision_blocks));
+		let to_paths = |vector: Vec<&EDElement>| vector.into_iter().map(|element| element.get_path().to_string()).collect();
+		let mut link_groups: Vec<DuplicateGroup> = link_dups
+			.into_iter()
+			.filter(|(_, vector)| vector.len() > 1)
+			.map(|(target, vector)| DuplicateGroup::Links { target: target.to_string(), paths: to_paths(vector) })
+			.collect();
+		let mut file_groups: Vec<DuplicateGroup> = file_dups
+			.into_iter()
+			.filter(|(_, vector)| vector.len() > 1)
+			.map(|(checksum, vector)| DuplicateGroup::Files { checksum: checksum.clone(), paths: to_paths(vector) })
+			.collect();
+		link_groups.sort_by(|a, b| a.paths()[0].cmp(&b.paths()[0]));
+		file_groups.sort_by(|a, b| a.paths()[0].cmp(&b.paths()[0]));
+		link_groups.append(&mut file_groups);
+		link_groups
 	}
 
-	/// Returns a complete list of all files
-	/// from the given root directory.
-	/// Does not follow symbolic links, but symbolic links are indexed
-	/// as a normal file.
+	/// Returns the paths of all files and links within path,
+	/// which are neither in the banlist, nor ignored by a .file_hasher_ignore file.
 	///
-	/// Does not index if, file is not a regular readable file, or a symbolic link.
-	/// Does not index paths that are in the banlist.
+	/// If the ignore files found while indexing differ from the accepted ignore files,
+	/// because an ignore file was added, changed or removed, an error is returned,
+	/// just like for a banlist with an invalid checksum.
 	fn index(&self, path: &str, interfacer: &impl UserInterface) -> Result<Vec<String>, IndexError> {
+		let mut found_ignore_files = Vec::new();
+		let index_list = self.index_directory(path, &mut Vec::new(), &mut found_ignore_files, interfacer)?;
+		if AcceptedIgnoreFiles::from_ignore_files(&found_ignore_files) != self.accepted_ignore_files {
+			return Err(IndexError::IgnoreFilesChanged(
+				found_ignore_files.iter().map(|file| file.get_path().to_string()).collect(),
+			));
+		}
+		Ok(index_list)
+	}
+
+	/// Returns the .file_hasher_ignore files, that are used when indexing the current directory.
+	fn find_ignore_files(&self, interfacer: &impl UserInterface) -> Result<Vec<IgnoreFile>, IndexError> {
+		let mut found_ignore_files = Vec::new();
+		self.index_directory(".", &mut Vec::new(), &mut found_ignore_files, interfacer)?;
+		Ok(found_ignore_files)
+	}
+
+	/// Searches the current directory for the .file_hasher_ignore files, and shows them to the user,
+	/// if they have changed since file_hashes was written, asking whether their paths and checksum
+	/// should be stored in the list, such that they can be used.
+	///
+	/// A changed ignore file may exclude files from the list, so like a banlist with an invalid checksum,
+	/// changed ignore files must be accepted, before the list can be opened or indexed.
+	pub fn accept_ignore_files(&mut self, interfacer: &impl UserInterface) -> Result<(), IndexError> {
+		let found_ignore_files = self.find_ignore_files(interfacer)?;
+		let accepted_ignore_files = AcceptedIgnoreFiles::from_ignore_files(&found_ignore_files);
+		if accepted_ignore_files == self.accepted_ignore_files {
+			self.ignore_files = found_ignore_files;
+			interfacer.send_message(&format!("The {} files have not changed", ignore_files::IGNORE_FILE_NAME));
+			return Ok(());
+		}
+
+		let ignore_file_paths = if found_ignore_files.is_empty() {
+			"There are no ignore files anymore.".to_string()
+		}
+		else {
+			found_ignore_files.iter().map(IgnoreFile::get_path).collect::<Vec<_>>().join("\n")
+		};
+		let answer: YesNo = interfacer.get_user_answer(&format!(
+			"The {} files have changed since file_hashes was written, the ignore files are now:\n{}\nDo you wish to accept them?",
+			ignore_files::IGNORE_FILE_NAME,
+			ignore_file_paths
+		));
+		if answer == YesNo::No {
+			return Err(IndexError::IgnoreFilesRejected);
+		}
+		self.accepted_ignore_files = accepted_ignore_files;
+		self.ignore_files = found_ignore_files;
+		interfacer.send_message(&format!("The {} files have been accepted", ignore_files::IGNORE_FILE_NAME));
+		Ok(())
+	}
+
+	/// Used internally by index to search a directory recursively.
+	/// The ignore files of the directory and its parents are stored in active_ignore_files,
+	/// every ignore file is moved to found_ignore_files, when its directory has been searched.
+	fn index_directory(
+		&self, path: &str, active_ignore_files: &mut Vec<IgnoreFile>, found_ignore_files: &mut Vec<IgnoreFile>,
+		interfacer: &impl UserInterface,
+	) -> Result<Vec<String>, IndexError> {
 		let entries = std::fs::read_dir(path).map_err(|err| IndexError::CantGetSubDirError(path.to_string(), err.to_string()))?;
 		let mut index_list: Vec<String> = Vec::new();
 
+		let ignore_file = IgnoreFile::read(&format!("{}/", path))?;
+		let has_ignore_file = ignore_file.is_some();
+		active_ignore_files.extend(ignore_file);
+
 		for entry in entries {
 			let entry = entry?;
 			let file_type = entry.file_type()?;
@@ -469,12 +1102,18 @@ impl EDList {
 				path,
 				entry.file_name().into_string().map_err(|_| IndexError::OsStringConvertError(path.to_string()))?
 			);
-			// If file_path is in banlist, we should not index it.
-			if self.banlist.is_in_banlist(&file_path) {
+			// If file_path is excluded by the banlist or a .file_hasher_ignore file, we should not index it.
+			// Excluded directories are only indexed, if a negation may include paths within them.
+			if file_type.is_dir() {
+				if ignore_files::is_directory_excluded(&self.banlist, active_ignore_files, &format!("{}/", file_path)) {
+					continue;
+				}
+			}
+			else if ignore_files::is_excluded(&self.banlist, active_ignore_files, &file_path) {
 				continue;
 			}
 			if file_type.is_dir() {
-				for element in self.index(&file_path, interfacer)? {
+				for element in self.index_directory(&file_path, active_ignore_files, found_ignore_files, interfacer)? {
 					index_list.push(element);
 				}
 			}
@@ -491,16 +1130,10 @@ impl EDList {
 				);
 			}
 		}
-		Ok(index_list)
-	}
-
-	fn get_version_from_line(line: &str) -> ListVersion {
-		match line.strip_prefix(LIST_VERSION_PREFIX) {
-			Some("1.1") => ListVersion::V1_1,
-			Some("1.0") => ListVersion::V1_0,
-			Some(identifier) => ListVersion::InvalidVersion(identifier),
-			None => ListVersion::MissingIdentifier,
+		if has_ignore_file {
+			found_ignore_files.extend(active_ignore_files.pop());
 		}
+		Ok(index_list)
 	}
 
 	/// This is the only method that must be used to add elements
@@ -511,51 +1144,128 @@ impl EDList {
 		self.element_list.push(element);
 	}
 
+	/// Replaces the element at the given index with a new element,
+	/// and returns the old element.
+	/// Like add_e_d_element, it handles updating the lists internal
+	/// xor checksum, and must be used when replacing elements.
+	fn replace_e_d_element(&mut self, index: usize, element: EDElement) -> EDElement {
+		let old_element = std::mem::replace(&mut self.element_list[index], element);
+		self.xor_checksum ^= old_element.get_hash();
+		self.xor_checksum ^= self.element_list[index].get_hash();
+		old_element
+	}
+
 	/// Write EDList to {root_path}/file_hasher_files/file_hashes
 	pub fn write_hash_file(&self) -> Result<(), WriteHashFileError> {
-		let mut file = File::create(format!("{}/file_hasher_files/file_hashes", self.root_path))
-			.map_err(|err| WriteHashFileError::ErrorCreatingFile(err.to_string()))?;
-		self.write_edlist_to_file(&mut file, "file_hashes")?;
+		let root_path = self.root_path.as_ref().ok_or(WriteHashFileError::ReadOnlyList)?;
+		self.write_edlist_to_file(&format!("{}/file_hasher_files/file_hashes", root_path), "file_hashes")?;
 		Ok(())
 	}
 
-	fn write_backup(&self) -> Result<(), WriteBackupError> {
-		let backup_dir = format!("{}/file_hasher_files/hash_file_backups", self.root_path);
+	fn write_backup(&self, root_path: &str) -> Result<(), WriteBackupError> {
+		let backup_dir = format!("{}/file_hasher_files/hash_file_backups", root_path);
 		create_dir_all(&backup_dir).map_err(|err| WriteBackupError::CreateDirectoryError(err.to_string()))?;
 		let local: DateTime<Local> = Local::now();
-		let mut file = File::create(format!("{}/{}", backup_dir, local.format("%Y-%m-%d %H.%M.%S.%f %z")))
-			.map_err(|err| WriteBackupError::CreateFileError(err.to_string()))?;
-		self.write_edlist_to_file(&mut file, "hashbackup")?;
+		self.write_edlist_to_file(&format!("{}/{}", backup_dir, local.format(BACKUP_TIMESTAMP_FORMAT)), "hashbackup")?;
 		Ok(())
 	}
 
-	/// Used when we need to write hash_file data to a file
-	/// Also used for writing the backups to file.
-	fn write_edlist_to_file(&self, file: &mut File, file_name: &str) -> Result<(), WriteEDListToFileError> {
-		let mut hasher = Blake2bVar::new(HASH_OUTPUT_LENGTH).unwrap();
-		let mut element_string = String::new();
-
-		for element in &self.element_list {
-			element_string.push_str(format!("{}\n", element).as_ref());
-			hasher.update(element.get_hash().as_ref());
+	/// Finds the differences between old_list and this list, such as
+	/// the differences between a backup and the current file_hashes.
+	///
+	/// Both lists must hash files using the same hash algorithm,
+	/// since the checksums of files are compared.
+	pub fn diff(&self, old_list: &EDList) -> Result<Vec<ListDifference>, DiffError> {
+		if old_list.hash_algorithm != self.hash_algorithm {
+			return Err(DiffError::HashAlgorithmMismatch(old_list.hash_algorithm, self.hash_algorithm));
 		}
-		hasher.update(self.xor_checksum.as_ref());
-
-		let list_version_string = format!("{}{}\n", LIST_VERSION_PREFIX, CURRENT_LIST_VERSION);
-		let xor_checksum_string = format!("{}{}\n", XOR_CHECKSUM_PREFIX, hex::encode_upper(&self.xor_checksum.as_ref()));
-		let fin_checksum_string = format!("{}{}\n", FIN_CHECKSUM_PREFIX, shared::blake2_to_checksum(hasher));
-
-		let final_string = format!("{}{}{}{}", list_version_string, xor_checksum_string, fin_checksum_string, element_string);
+		Ok(list_diff::diff(&old_list.element_list, &self.element_list))
+	}
 
-		file.write_all(final_string.as_bytes())
-			.map_err(|err| WriteEDListToFileError::WriteError(file_name.to_string(), err.to_string()))?;
+	/// Deletes the backups in hash_file_backups that the policy doesn't keep,
+	/// after showing them to the user and asking for confirmation.
+	///
+	/// The backups are validated newest first, and the first backup that validates
+	/// is always kept, such that pruning never deletes the only backup
+	/// file_hashes can be restored from.
+	pub fn prune_backups(&self, policy: &RetentionPolicy, user_interface: &impl UserInterface) -> Result<(), PruneBackupsError> {
+		let root_path = self.root_path.as_ref().ok_or(PruneBackupsError::ReadOnlyList)?;
+		let backup_dir = format!("{}/file_hasher_files/hash_file_backups", root_path);
+		let backup_list = backups::list_backups(Path::new(&backup_dir)).map_err(PruneBackupsError::ReadBackupsError)?;
+
+		let mut keep = backups::backups_to_keep(&backup_list, policy);
+		let newest_valid = backup_list.iter().position(|backup| {
+			File::open(&backup.path)
+				.map_err(EDListOpenError::from)
+				.and_then(|file| list_format::read_list(BufReader::new(file)))
+				.is_ok()
+		});
+		match newest_valid {
+			Some(index) => keep[index] = true,
+			None => user_interface.send_message("None of the backups of file_hashes are valid"),
+		}
+		let mut deletions = backup_list.iter().zip(keep).filter(|(_, keep)| !keep).map(|(backup, _)| backup).collect::<Vec<_>>();
+
+		let sync_backup_list = match policy.keep_sync_backups {
+			Some(keep_sync_backups) => {
+				let mut sync_backup_list =
+					backups::list_sync_backups(Path::new(&backup_dir)).map_err(PruneBackupsError::ReadBackupsError)?;
+				sync_backup_list.drain(..keep_sync_backups.min(sync_backup_list.len()));
+				sync_backup_list
+			},
+			None => Vec::new(),
+		};
+		deletions.extend(sync_backup_list.iter());
 
-		file.flush()
-			.map_err(|err| WriteEDListToFileError::FlushError(file_name.to_string(), err.to_string()))?;
+		if deletions.is_empty() {
+			user_interface.send_message("No backups need to be deleted");
+			return Ok(());
+		}
+		let mut message = String::from("These backups will be deleted:\n");
+		for backup in &deletions {
+			message.push_str(&format!("\t{}\n", backup.name));
+		}
+		let freed_size: u64 = deletions.iter().map(|backup| backup.size).sum();
+		message.push_str(&format!("Deleting the backups of file_hashes frees {} bytes", freed_size));
+		user_interface.send_message(&message);
 
+		let answer: YesNo = user_interface.get_user_answer(&format!("Do you wish to delete {} backups?", deletions.len()));
+		if answer == YesNo::No {
+			return Ok(());
+		}
+		for backup in &deletions {
+			let result = if backup.path.is_dir() {
+				std::fs::remove_dir_all(&backup.path)
+			}
+			else {
+				std::fs::remove_file(&backup.path)
+			};
+			result.map_err(|err| PruneBackupsError::DeleteError(backup.name.clone(), err))?;
+		}
+		user_interface.send_message(&format!("Deleted {} backups", deletions.len()));
 		Ok(())
 	}
 
+	/// Used when we need to write hash_file data to a file
+	/// Also used for writing the backups to file.
+	///
+	/// The file is written atomically, so a crash while writing
+	/// never leaves a partially written list at path.
+	fn write_edlist_to_file(&self, path: &str, file_name: &str) -> Result<(), WriteEDListToFileError> {
+		shared::write_file_atomically_with(Path::new(path), |writer| {
+			list_format::write_list(
+				writer,
+				&self.element_list,
+				&self.xor_checksum,
+				self.hash_algorithm,
+				&self.accepted_ignore_files,
+				self.format,
+				self.compression,
+			)
+		})
+		.map_err(|err| WriteEDListToFileError::WriteError(file_name.to_string(), err.to_string()))
+	}
+
 	/// Used to generate a checksum, using only EDElemnts
 	/// whose path contains the relative path.
 	///
@@ -566,10 +1276,8 @@ impl EDList {
 	/// included in the generated checksum.
 	/// This makes it possible to compare to another different
 	/// paths checksum.
-	pub fn relative_checksum(&self, user_interface: &impl UserInterface) {
-		let SlashEnding { path: relative_path } = user_interface.get_user_answer("Enter the relative path:");
-
-		if let Some(hash) = self.internal_relative_checksum(relative_path.as_str(), false) {
+	pub fn relative_checksum(&self, relative_path: &str, user_interface: &impl UserInterface) {
+		if let Some(hash) = self.get_relative_checksum(relative_path) {
 			user_interface.send_message(&format!("Relative hash:\n{}", hash));
 		}
 		else {
@@ -577,6 +1285,16 @@ impl EDList {
 		}
 	}
 
+	/// Returns the checksum of every element whose path starts with relative_path,
+	/// calculated from the paths relative to relative_path,
+	/// or None if there are no such elements.
+	///
+	/// Two lists have the same relative checksum for two relative paths,
+	/// when the elements under them are the same.
+	pub fn get_relative_checksum(&self, relative_path: &str) -> Option<Checksum> {
+		self.internal_relative_checksum(relative_path, false)
+	}
+
 	fn internal_relative_checksum(&self, relative_path: &str, no_elements_allowed: bool) -> Option<Checksum> {
 		let mut hasher = Blake2bVar::new(HASH_OUTPUT_LENGTH).unwrap();
 		let mut elements_found = false;
@@ -586,9 +1304,9 @@ impl EDList {
 			.for_each(|(e_d_element, postfix)| {
 				elements_found = true;
 				hasher.update(postfix.as_bytes());
-				hasher.update(&e_d_element.get_modified_time().to_le_bytes());
+				e_d_element.get_modified_time().update_hasher(&mut hasher);
 				match e_d_element.get_variant() {
-					e_d_element::EDVariantFields::File { checksum } => hasher.update(checksum.as_ref()),
+					e_d_element::EDVariantFields::File { checksum, size: _ } => hasher.update(checksum.as_ref()),
 					e_d_element::EDVariantFields::Link { target } => hasher.update(target.as_bytes()),
 				}
 			});
@@ -602,9 +1320,9 @@ impl EDList {
 			.filter(|e_d_element| e_d_element.get_path().strip_prefix(relative_path).is_none())
 			.for_each(|e_d_element| {
 				hasher.update(e_d_element.get_path().as_bytes());
-				hasher.update(&e_d_element.get_modified_time().to_le_bytes());
+				e_d_element.get_modified_time().update_hasher(&mut hasher);
 				match e_d_element.get_variant() {
-					e_d_element::EDVariantFields::File { checksum } => hasher.update(checksum.as_ref()),
+					e_d_element::EDVariantFields::File { checksum, size: _ } => hasher.update(checksum.as_ref()),
 					e_d_element::EDVariantFields::Link { target } => hasher.update(target.as_bytes()),
 				}
 			});
@@ -613,23 +1331,35 @@ impl EDList {
 
 	/// Deletes all empty folders within the given root directory.
 	///
-	/// Ignores folders that is in the given banlist.
+	/// Ignores folders that are excluded by the given banlist or ignore files, folders within
+	/// them are only deleted if they are included by a negation, see ignore_files::is_excluded.
 	///
 	/// Also tells the user through user_interface, which folders were deleted.
-	fn delete_empty_folders(path: &Path, banlist: &PathBanlist, user_interface: &impl UserInterface) -> Result<bool, SyncFromError> {
+	fn delete_empty_folders(
+		path: &Path, banlist: &PathBanlist, ignore_files: &[IgnoreFile], user_interface: &impl UserInterface,
+	) -> Result<bool, SyncFromError> {
 		let mut files_or_banlist_found = false;
 
 		for entry in std::fs::read_dir(path)? {
 			let entry_path = entry?.path();
 			if entry_path.is_dir() &&
-				!banlist.is_in_banlist(&format!("{}/", entry_path.to_str().expect("Folders with non utf-8 names is not supported!")))
-			{
-				files_or_banlist_found = EDList::delete_empty_folders(&entry_path, banlist, user_interface)? || files_or_banlist_found;
+				!ignore_files::is_directory_excluded(
+					banlist,
+					ignore_files,
+					&format!("{}/", entry_path.to_str().expect("Folders with non utf-8 names is not supported!")),
+				) {
+				files_or_banlist_found =
+					EDList::delete_empty_folders(&entry_path, banlist, ignore_files, user_interface)? || files_or_banlist_found;
 			}
 			else {
 				files_or_banlist_found = true;
 			}
 		}
+		if !files_or_banlist_found &&
+			ignore_files::is_excluded(banlist, ignore_files, &format!("{}/", path.to_str().unwrap().trim_end_matches('/')))
+		{
+			files_or_banlist_found = true;
+		}
 		if !files_or_banlist_found {
 			user_interface.send_message(&format!("Deleting folder {}", path.to_str().unwrap()));
 			std::fs::remove_dir(path)?;
@@ -637,6 +1367,15 @@ impl EDList {
 		Ok(files_or_banlist_found)
 	}
 
+	/// Returns whether any of the elements under prefix has all of its metadata,
+	/// and whether any of them is missing some of it, see EDElement::has_all_metadata.
+	fn metadata_completeness(element_list: &[EDElement], prefix: &str) -> (bool, bool) {
+		let mut elements = element_list.iter().filter(|element| element.get_path().starts_with(prefix));
+		let complete = elements.clone().any(EDElement::has_all_metadata);
+		let incomplete = elements.any(|element| !element.has_all_metadata());
+		(complete, incomplete)
+	}
+
 	/// Executes a list of IO Fileoperations.
 	///
 	/// This operation modifies the real Filesystem, so use with care.
@@ -659,14 +1398,17 @@ impl EDList {
 			synclist.write_all(op_string.as_bytes())?;
 		}
 
+		let mut bytes_processed = 0;
 		for (i, operation) in operations.iter().enumerate() {
-			user_interface.send_message(&format!(
-				"operation {:0width$} of {}: {}",
-				i + 1,
-				operations.len(),
-				operation,
-				width = operations_length_width
-			));
+			if !user_interface.shows_progress() {
+				user_interface.send_message(&format!(
+					"operation {:0width$} of {}: {}",
+					i + 1,
+					operations.len(),
+					operation,
+					width = operations_length_width
+				));
+			}
 			match operation {
 				Delete(path) => {
 					fs::create_dir_all(format!("{}{}", &backup_folder, Path::new(path).parent().unwrap().to_str().unwrap()))?;
@@ -682,7 +1424,7 @@ impl EDList {
 					fs::create_dir_all(dir)?;
 					let metadata = fs::symlink_metadata(from)?;
 					if metadata.is_file() {
-						std::fs::copy(from, to)?;
+						bytes_processed += std::fs::copy(from, to)?;
 					}
 					else {
 						match fs::read_link(from).unwrap().to_str() {
@@ -704,55 +1446,101 @@ impl EDList {
 					set_symlink_file_times(to, created_time, modified_time)?;
 				},
 			}
+			let path = match operation {
+				Delete(path) => path,
+				Move { from: _, to } | Copy { from: _, to } => to,
+			};
+			user_interface.send_progress(&ProgressEvent {
+				phase: ProgressPhase::FileOperations,
+				current: i + 1,
+				total: operations.len(),
+				bytes_processed,
+				path,
+			});
 		}
 		Ok(())
 	}
 
-	/// Attempts to syncronise another EDLists relative path to the currents relative path
-	/// as given by the user.
-	pub fn sync(&mut self, user_interface: &impl UserInterface) -> Result<(), SyncFromError> {
-		use std::mem;
-
-		use itertools::{Either, Itertools};
-
+	/// Attempts to syncronise another EDLists relative path to the currents relative path.
+	///
+	/// source_folder_path is the root folder of the other EDList, sync_to_prefix is the
+	/// relative path in this EDList that will be synced to, and sync_from_prefix is the
+	/// relative path in the other EDList that will be synced from.
+	/// All three paths must end with a forward slash.
+	///
+	/// The user is asked through user_interface, before the planned operations are done.
+	pub fn sync(
+		&mut self, source_folder_path: &str, sync_to_prefix: &str, sync_from_prefix: &str, user_interface: &impl UserInterface,
+	) -> Result<(), SyncFromError> {
 		user_interface.send_message(
 			"Warning, this operation can be dangerous to your target directory.\nShould an issue occur the file_hashes list will be \
 			 backed up in the file_hasher_files directory.\nDeleted files and information about actions done will be placed here as \
 			 well.\nThis also doesn't copy the banlist of the source list.",
 		);
 
-		let SlashEnding { path: source_folder_path } = user_interface.get_user_answer("Enter path to other folder indexed by file_hasher:");
-		let mut source_e_d_list = EDList::open(&source_folder_path, &StubUserInterface::new("NO".to_string()), PathBanlist::new_dummy())?;
-		let SlashEnding { path: sync_to_prefix } =
-			user_interface.get_user_answer("Enter relative path from the current edlist, where you will sync to:");
-		let SlashEnding { path: sync_from_prefix } =
-			user_interface.get_user_answer("Enter relative path from the external edlist, where you will sync from");
+		let sync_plan = self.plan_sync(source_folder_path, sync_to_prefix, sync_from_prefix)?;
 
-		std::fs::create_dir_all(&sync_to_prefix)?;
+		std::fs::create_dir_all(sync_to_prefix)?;
 		let user_answer: YesNo = user_interface.get_user_answer(&format!(
 			"Sync from {:?} -> {:?}.\nIs this ok?",
 			canonicalize(format!("{}{}", source_folder_path, sync_from_prefix))?,
-			canonicalize(&sync_to_prefix)?
+			canonicalize(sync_to_prefix)?
 		));
 		if user_answer == YesNo::No {
 			return Err(SyncFromError::UserAbort);
 		}
-		let target_element_list_backup = self.element_list.clone();
-		let target_xor_checksum_backup = self.xor_checksum;
 
-		let source_relative_checksum = source_e_d_list.internal_relative_checksum(sync_from_prefix.as_str(), true).unwrap();
-		let target_negated_relative_checksum = self.internal_negated_relative_checksum(sync_to_prefix.as_str());
+		user_interface.send_message("These operations will be done:");
+		sync_plan.get_operations().for_each(|operation| user_interface.send_message(&operation.to_string()));
+
+		if user_interface.get_user_answer::<YesNo>("Do you want to continue?") == YesNo::No {
+			return Err(SyncFromError::UserAbort);
+		}
+		self.apply_sync_plan(sync_plan, user_interface)
+	}
+
+	/// Plans the syncronisation of another EDLists relative path to the currents relative path,
+	/// without modifying this list or the filesystem.
+	/// The other EDList is opened read-only, so no backup of it is written.
+	///
+	/// The arguments are the same as for sync.
+	/// The planned list is validated using relative checksums, before the plan is returned.
+	///
+	/// Unchanged files are only found, if both lists store the same metadata of the elements,
+	/// so the sync is refused if one of the lists has elements without the metadata the other list stores.
+	pub fn plan_sync(&self, source_folder_path: &str, sync_to_prefix: &str, sync_from_prefix: &str) -> Result<SyncPlan, SyncFromError> {
+		use std::mem;
+
+		use itertools::{Either, Itertools};
+
+		let mut source_e_d_list = EDList::open_read_only(&format!("{}/file_hasher_files/file_hashes", source_folder_path))?;
+		if source_e_d_list.hash_algorithm != self.hash_algorithm {
+			return Err(SyncFromError::HashAlgorithmMismatch(source_e_d_list.hash_algorithm, self.hash_algorithm));
+		}
+		let (source_complete, source_incomplete) = EDList::metadata_completeness(&source_e_d_list.element_list, sync_from_prefix);
+		let (target_complete, target_incomplete) = EDList::metadata_completeness(&self.element_list, sync_to_prefix);
+		if source_incomplete && target_complete {
+			return Err(SyncFromError::SourceMetadataMissing);
+		}
+		if target_incomplete && source_complete {
+			return Err(SyncFromError::TargetMetadataMissing);
+		}
+
+		let source_relative_checksum = source_e_d_list.internal_relative_checksum(sync_from_prefix, true).unwrap();
+		let target_negated_relative_checksum = self.internal_negated_relative_checksum(sync_to_prefix);
 
-		let (target_list, existing_files_vec): (Vec<_>, Vec<_>) = mem::take(&mut self.element_list).into_iter().partition_map(|element| {
-			if element.get_path().strip_prefix(sync_to_prefix.as_str()).is_some() {
-				self.xor_checksum ^= element.get_hash();
+		let mut target = self.clone();
+		let target_elements = mem::take(&mut target.element_list);
+		let (target_list, existing_files_vec): (Vec<_>, Vec<_>) = target_elements.into_iter().partition_map(|element| {
+			if element.get_path().strip_prefix(sync_to_prefix).is_some() {
+				target.xor_checksum ^= element.get_hash();
 				Either::Right(element)
 			}
 			else {
 				Either::Left(element)
 			}
 		});
-		self.element_list = target_list;
+		target.element_list = target_list;
 
 		let mut existing_files_map = existing_files_vec.into_iter().fold(HashMap::new(), |mut map: HashMap<_, Vec<_>>, element| {
 			map.entry((element.get_variant().clone(), element.get_modified_time())).or_default().push(element);
@@ -761,7 +1549,7 @@ impl EDList {
 
 		let source_iter = mem::take(&mut source_e_d_list.element_list)
 			.into_iter()
-			.filter(|element| element.get_path().strip_prefix(sync_from_prefix.as_str()).is_some());
+			.filter(|element| element.get_path().strip_prefix(sync_from_prefix).is_some());
 
 		let mut pre_file_operations = Vec::new(); // Moving files before they can be overwritten.
 		let mut post_file_operations = Vec::new();
@@ -773,17 +1561,17 @@ impl EDList {
 				.unwrap_or(&mut empty_dummy_vec);
 			let exact_match = existing_files
 				.drain_filter(|existing_element| {
-					let prefix_stripped_source = source_element.get_path().strip_prefix(sync_from_prefix.as_str()).unwrap();
-					let prefix_stripped_target = existing_element.get_path().strip_prefix(sync_to_prefix.as_str()).unwrap();
+					let prefix_stripped_source = source_element.get_path().strip_prefix(sync_from_prefix).unwrap();
+					let prefix_stripped_target = existing_element.get_path().strip_prefix(sync_to_prefix).unwrap();
 					// Since paths are unique, there can only be up to one collision.
 					prefix_stripped_source == prefix_stripped_target
 				})
 				.next();
 			if let Some(exact_match) = exact_match {
-				self.add_e_d_element(exact_match);
+				target.add_e_d_element(exact_match);
 			}
 			else {
-				let prefix_stripped_source = source_element.get_path().strip_prefix(sync_from_prefix.as_str()).unwrap();
+				let prefix_stripped_source = source_element.get_path().strip_prefix(sync_from_prefix).unwrap();
 				let dest_path = format!("{}{}", sync_to_prefix, prefix_stripped_source);
 				if let Some(mut existing_element) = existing_files.pop() {
 					// File exists in target list, but has a different path.
@@ -794,7 +1582,7 @@ impl EDList {
 					post_file_operations.push(FileOperation::Move { from: temp_path, to: dest_path.clone() });
 					// Modify element
 					existing_element.update_path(dest_path);
-					self.add_e_d_element(existing_element);
+					target.add_e_d_element(existing_element);
 				}
 				else {
 					// Element doesn't exist in target list.
@@ -804,7 +1592,7 @@ impl EDList {
 						to:   dest_path.clone(),
 					});
 					source_element.update_path(dest_path);
-					self.add_e_d_element(source_element);
+					target.add_e_d_element(source_element);
 				}
 			}
 		});
@@ -814,45 +1602,206 @@ impl EDList {
 			pre_file_operations.push(FileOperation::Delete(element.take_path()));
 		});
 
-		let target_relative_checksum = self.internal_relative_checksum(sync_to_prefix.as_str(), true).unwrap();
-		let new_target_negated_relative_checksum = self.internal_negated_relative_checksum(sync_to_prefix.as_str());
+		let target_relative_checksum = target.internal_relative_checksum(sync_to_prefix, true).unwrap();
+		let new_target_negated_relative_checksum = target.internal_negated_relative_checksum(sync_to_prefix);
 
 		if source_relative_checksum != target_relative_checksum || new_target_negated_relative_checksum != target_negated_relative_checksum
 		{
-			return Err(SyncFromError::ChecksumValidationError {
+			return Err(SyncFromError::ChecksumValidationError(Box::new(SyncChecksums {
 				source_rel:      source_relative_checksum,
 				target_rel:      target_relative_checksum,
 				new_negated_rel: new_target_negated_relative_checksum,
 				negated_rel:     target_negated_relative_checksum,
-			});
+			})));
 		}
 
-		user_interface.send_message("These operations will be done:");
-		let print_operation = |operation: &FileOperation| user_interface.send_message(&operation.to_string());
-		pre_file_operations.iter().for_each(print_operation);
-		post_file_operations.iter().for_each(print_operation);
+		Ok(SyncPlan {
+			source_folder_path: source_folder_path.to_string(),
+			sync_to_prefix: sync_to_prefix.to_string(),
+			sync_from_prefix: sync_from_prefix.to_string(),
+			pre_file_operations,
+			post_file_operations,
+			files_moved,
+			element_list: target.element_list,
+			xor_checksum: target.xor_checksum,
+			base_xor_checksum: self.xor_checksum,
+			base_length: self.element_list.len(),
+		})
+	}
 
-		if user_interface.get_user_answer::<YesNo>("Do you want to continue?") == YesNo::No {
-			self.element_list = target_element_list_backup;
-			self.xor_checksum = target_xor_checksum_backup;
-			return Err(SyncFromError::UserAbort);
+	/// Applies a sync plan created by plan_sync to this list and the filesystem.
+	///
+	/// The user_interface is only used for telling the user which operations are done,
+	/// the user isn't asked any questions.
+	///
+	/// This operation modifies the real Filesystem, so use with care.
+	pub fn apply_sync_plan(&mut self, sync_plan: SyncPlan, user_interface: &impl UserInterface) -> Result<(), SyncFromError> {
+		if sync_plan.base_xor_checksum != self.xor_checksum || sync_plan.base_length != self.element_list.len() {
+			return Err(SyncFromError::OutdatedSyncPlan);
 		}
+		self.element_list = sync_plan.element_list;
+		self.xor_checksum = sync_plan.xor_checksum;
 
 		let backup_folder = format!("./file_hasher_files/hash_file_backups/syncbackup-{}/", Local::now());
 		std::fs::create_dir_all(&backup_folder)?;
 
-		EDList::do_file_operations(&pre_file_operations, user_interface, &backup_folder)?;
-		EDList::delete_empty_folders(Path::new("./"), &self.banlist, user_interface)?;
-		EDList::do_file_operations(&post_file_operations, user_interface, &backup_folder)?;
-		EDList::delete_empty_folders(Path::new("./"), &self.banlist, user_interface)?;
-		if files_moved {
-			EDList::delete_empty_folders(Path::new(TMPCOPYDIR), &PathBanlist::new_dummy(), user_interface)?;
+		EDList::do_file_operations(&sync_plan.pre_file_operations, user_interface, &backup_folder)?;
+		EDList::delete_empty_folders(Path::new("./"), &self.banlist, &self.ignore_files, user_interface)?;
+		EDList::do_file_operations(&sync_plan.post_file_operations, user_interface, &backup_folder)?;
+		EDList::delete_empty_folders(Path::new("./"), &self.banlist, &self.ignore_files, user_interface)?;
+		if sync_plan.files_moved {
+			EDList::delete_empty_folders(Path::new(TMPCOPYDIR), &PathBanlist::new_dummy(), &[], user_interface)?;
 		}
 		Ok(())
 	}
 
+	/// Exports the files in the list whose paths start with prefix,
+	/// as a manifest that can be checked using the coreutils checksum
+	/// program of the hash algorithm of the list, like sha256sum or b2sum.
+	///
+	/// Links can't be stored in manifests, so they are skipped,
+	/// and the amount of skipped links is sent to the user_interface.
+	pub fn export_manifest(&self, prefix: &str, user_interface: &impl UserInterface) -> String {
+		let mut manifest = String::new();
+		let mut skipped_links = 0;
+		for element in self.element_list.iter().filter(|e| e.get_path().strip_prefix(prefix).is_some()) {
+			match element.get_variant() {
+				EDVariantFields::File { checksum, size: _ } => manifest.push_str(&manifest::format_line(checksum, element.get_path())),
+				EDVariantFields::Link { target: _ } => skipped_links += 1,
+			}
+		}
+		if skipped_links > 0 {
+			user_interface.send_message(&format!("Skipped {} links, links can't be stored in manifests", skipped_links));
+		}
+		manifest
+	}
+
+	/// Adds the files of the manifest at manifest_path, that are not yet
+	/// in the list, to the list.
+	/// The checksums of the manifest are stored without hashing the files,
+	/// so files that don't match the manifest are found when the list is verified.
+	///
+	/// hash_algorithm is the algorithm that made the checksums of the manifest,
+	/// it must be the hash algorithm of the list, since the checksums can't be
+	/// told apart from checksums of another algorithm with the same length.
+	/// Returns an error for every line of the manifest that could not be imported.
+	pub fn import_manifest(
+		&mut self, manifest_path: &str, hash_algorithm: HashAlgorithm, user_interface: &impl UserInterface,
+	) -> Result<Vec<ManifestLineError>, ReadManifestError> {
+		if hash_algorithm != self.hash_algorithm {
+			return Err(ReadManifestError::HashAlgorithmMismatch(hash_algorithm, self.hash_algorithm));
+		}
+		let (entries, mut errors) = manifest::read_manifest_file(manifest_path)?;
+		let mut existing_paths: std::collections::HashSet<_> = self.element_list.iter().map(|e| e.get_path().to_string()).collect();
+		let mut imported_files = 0;
+		for entry in entries {
+			if self.is_excluded(&entry.path) {
+				errors.push(ManifestLineError::PathInBanlist(entry.line, entry.path));
+				continue;
+			}
+			if existing_paths.contains(&entry.path) {
+				errors.push(ManifestLineError::PathInList(entry.line, entry.path));
+				continue;
+			}
+			if entry.checksum.len() != self.hash_algorithm.output_length() {
+				errors.push(ManifestLineError::ChecksumLengthMismatch(entry.line, entry.path));
+				continue;
+			}
+			let metadata = match std::fs::symlink_metadata(&entry.path) {
+				Ok(metadata) => metadata,
+				Err(err) => {
+					errors.push(ManifestLineError::EDElementError(entry.line, EDElementError::GetMetaDataError(entry.path, err)));
+					continue;
+				},
+			};
+			if !metadata.is_file() {
+				errors.push(ManifestLineError::NotAFile(entry.line, entry.path));
+				continue;
+			}
+			existing_paths.insert(entry.path.clone());
+			self.add_e_d_element(EDElement::from_checksum(entry.path, &metadata, entry.checksum));
+			imported_files += 1;
+		}
+		user_interface.send_message(&format!("Imported files, amount = {}", imported_files));
+		errors.sort_by_key(|err| err.line());
+		Ok(errors)
+	}
+
+	/// Verifies the files of the manifest at manifest_path against
+	/// the checksums of the manifest, without using an EDList.
+	/// The files are hashed using hash_algorithm and the given amount
+	/// of threads, if threads is 0, a thread is used for every logical cpu.
+	///
+	/// Returns an error for every line of the manifest that could not be
+	/// verified, or whose file doesn't match, in the order of the manifest.
+	pub fn verify_manifest(
+		manifest_path: &str, hash_algorithm: HashAlgorithm, threads: usize, user_interface: &impl UserInterface,
+	) -> Result<Vec<ManifestLineError>, ReadManifestError> {
+		let (entries, mut errors) = manifest::read_manifest_file(manifest_path)?;
+		let (entries, invalid_entries): (Vec<_>, Vec<_>) =
+			entries.into_iter().partition(|entry| entry.checksum.len() == hash_algorithm.output_length());
+		errors.extend(
+			invalid_entries
+				.into_iter()
+				.map(|entry| ManifestLineError::ChecksumLengthMismatch(entry.line, entry.path)),
+		);
+
+		let entries_length = entries.len();
+		let entries_length_width = entries_length.to_string().chars().count();
+		let mut file_count = 0;
+		let mut bytes_processed = 0;
+		let pool = shared::build_thread_pool(threads);
+		shared::run_in_thread_pool(
+			&pool,
+			entries,
+			|entry| {
+				let result = EDList::verify_manifest_entry(&entry, hash_algorithm);
+				(entry, result)
+			},
+			|(entry, result)| {
+				file_count += 1;
+				if let Ok(size) = &result {
+					bytes_processed += size;
+				}
+				if !user_interface.shows_progress() {
+					user_interface.send_message(&format!(
+						"Verified file {:0width$} of {} = {}",
+						file_count,
+						entries_length,
+						entry.path,
+						width = entries_length_width
+					));
+				}
+				user_interface.send_progress(&ProgressEvent {
+					phase: ProgressPhase::Verifying,
+					current: file_count,
+					total: entries_length,
+					bytes_processed,
+					path: &entry.path,
+				});
+				if let Err(err) = result {
+					errors.push(ManifestLineError::EDElementError(entry.line, err));
+				}
+			},
+		);
+		errors.sort_by_key(|err| err.line());
+		Ok(errors)
+	}
+
+	/// Returns the size of the verified file.
+	fn verify_manifest_entry(entry: &ManifestEntry, hash_algorithm: HashAlgorithm) -> Result<u64, EDElementError> {
+		let mut file = File::open(&entry.path).map_err(|err| EDElementError::OpenFileError(entry.path.to_owned(), err))?;
+		let checksum =
+			EDElement::hash_file(&mut file, hash_algorithm).map_err(|err| EDElementError::FileHashingError(entry.path.to_owned(), err))?;
+		if checksum != entry.checksum {
+			Err(EDElementVerifyError::InvalidChecksum(entry.path.to_owned()))?
+		}
+		Ok(file.metadata().map(|metadata| metadata.len()).unwrap_or(0))
+	}
+
 	/// Performs a benchmark of the hashing performance of the computer
-	/// running it.
+	/// running it, for every supported hash algorithm, so that the
+	/// algorithms can be compared.
 	///
 	/// Will not modify the contents of the EDList at all.
 	pub fn benchmark(user_interface: &impl UserInterface, bytes: usize) {
@@ -872,27 +1821,33 @@ impl EDList {
 			}
 		}
 
-		let mut mock_file = ReadMock { bytes_left: bytes };
-		user_interface.send_message("Now benchmarking...");
-
-		let before = std::time::Instant::now();
-		let checksum = EDElement::hash_file(&mut mock_file).unwrap();
-		let time_elapsed_sec = before.elapsed().as_secs_f64();
-
-		user_interface.send_message(&format!("resulting hash = {}", checksum));
+		let mut results = Vec::new();
+		for hash_algorithm in HashAlgorithm::ALL {
+			let mut mock_file = ReadMock { bytes_left: bytes };
+			user_interface.send_message(&format!("Now benchmarking {}...", hash_algorithm));
 
-		let units = ["Bytes", "KiB", "MiB", "GiB"];
+			let before = std::time::Instant::now();
+			let checksum = EDElement::hash_file(&mut mock_file, hash_algorithm).unwrap();
+			let time_elapsed_sec = before.elapsed().as_secs_f64();
 
-		let mut cur_unit_over_time = bytes as f64 / time_elapsed_sec;
+			user_interface.send_message(&format!("resulting hash = {}", checksum));
+			results.push((hash_algorithm, bytes as f64 / time_elapsed_sec / (1024f64 * 1024f64)));
+		}
 
-		let length = format!("{:.2}", cur_unit_over_time).len();
+		let algorithm_width = HashAlgorithm::ALL.iter().map(|hash_algorithm| hash_algorithm.identifier().len()).max().unwrap_or(0);
+		let speed_width = results.iter().map(|(_, speed)| format!("{:.2}", speed).len()).max().unwrap_or(0);
 		let mut longest = 0;
-		for unit in units.iter() {
-			let result = format!("|{: <width$.2} {: <width2$} hashed a second|", cur_unit_over_time, unit, width = length, width2 = 5);
+		for (hash_algorithm, speed) in results {
+			let result = format!(
+				"|{: <width$} {: >width2$.2} MiB hashed a second|",
+				hash_algorithm.identifier(),
+				speed,
+				width = algorithm_width,
+				width2 = speed_width
+			);
 			longest = longest.max(result.len());
 			user_interface.send_message(&format!("|{:-<width$}|", "", width = longest - 2));
 			user_interface.send_message(&result);
-			cur_unit_over_time /= 1024f64;
 		}
 		user_interface.send_message(&format!("|{:-<width$}|", "", width = longest - 2));
 	}
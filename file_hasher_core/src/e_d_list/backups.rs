@@ -0,0 +1,157 @@
+/*
+	This file is part of file_hasher.
+
+	file_hasher is free software: you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation, either version 3 of the License, or
+	(at your option) any later version.
+
+	file_hasher is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License
+	along with file_hasher.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{
+	collections::HashSet,
+	fs, io,
+	path::{Path, PathBuf},
+};
+
+use chrono::prelude::{DateTime, FixedOffset, Local};
+
+use crate::shared::constants::*;
+
+/// The prefix of the folders, that sync moves deleted and overwritten files to.
+const SYNC_BACKUP_PREFIX: &str = "syncbackup-";
+/// The format of the timestamps in the names of the syncbackup folders.
+const SYNC_BACKUP_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f %:z";
+
+/// RetentionPolicy decides which backups in hash_file_backups are kept by EDList::prune_backups.
+///
+/// A backup of file_hashes is kept if it is one of the keep_last newest backups,
+/// or if it is the newest backup of one of the keep_daily newest days,
+/// keep_weekly newest weeks or keep_monthly newest months that have backups.
+/// If max_total_size is set, the oldest of those backups are deleted as well,
+/// until the size of the kept backups is at most max_total_size bytes.
+///
+/// The syncbackup folders contain the files that sync deleted or overwrote,
+/// so they are only pruned if keep_sync_backups is set, in which case
+/// the keep_sync_backups newest folders are kept.
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+	pub keep_last:         usize,
+	pub keep_daily:        usize,
+	pub keep_weekly:       usize,
+	pub keep_monthly:      usize,
+	pub max_total_size:    Option<u64>,
+	pub keep_sync_backups: Option<usize>,
+}
+impl Default for RetentionPolicy {
+	fn default() -> RetentionPolicy {
+		RetentionPolicy {
+			keep_last:         10,
+			keep_daily:        7,
+			keep_weekly:       4,
+			keep_monthly:      12,
+			max_total_size:    None,
+			keep_sync_backups: None,
+		}
+	}
+}
+
+/// BackupFile is a backup in the hash_file_backups folder,
+/// either a file_hashes file or a syncbackup folder.
+///
+/// The size is only known for file_hashes backups.
+pub(super) struct BackupFile {
+	pub name: String,
+	pub path: PathBuf,
+	pub time: DateTime<FixedOffset>,
+	pub size: u64,
+}
+
+/// Lists the backups of file_hashes in backup_dir, newest first.
+///
+/// The time of a backup is read from its name, backups whose names
+/// aren't timestamps use the modified time of the file instead.
+/// Folders, like the syncbackup folders, and hidden files, like
+/// the temporary files of an interrupted write, are not backups.
+pub(super) fn list_backups(backup_dir: &Path) -> io::Result<Vec<BackupFile>> {
+	let mut backups = Vec::new();
+	for entry in fs::read_dir(backup_dir)? {
+		let entry = entry?;
+		let name = entry.file_name().to_string_lossy().into_owned();
+		if name.starts_with('.') || !entry.file_type()?.is_file() {
+			continue;
+		}
+		let metadata = entry.metadata()?;
+		let time = match DateTime::parse_from_str(&name, BACKUP_TIMESTAMP_FORMAT) {
+			Ok(time) => time,
+			Err(_) => DateTime::<Local>::from(metadata.modified()?).into(),
+		};
+		backups.push(BackupFile { name, path: entry.path(), time, size: metadata.len() });
+	}
+	sort_newest_first(&mut backups);
+	Ok(backups)
+}
+
+/// Lists the syncbackup folders in backup_dir, newest first.
+pub(super) fn list_sync_backups(backup_dir: &Path) -> io::Result<Vec<BackupFile>> {
+	let mut backups = Vec::new();
+	for entry in fs::read_dir(backup_dir)? {
+		let entry = entry?;
+		let name = entry.file_name().to_string_lossy().into_owned();
+		let timestamp = match name.strip_prefix(SYNC_BACKUP_PREFIX) {
+			Some(timestamp) if entry.file_type()?.is_dir() => timestamp,
+			_ => continue,
+		};
+		let time = match DateTime::parse_from_str(timestamp, SYNC_BACKUP_TIMESTAMP_FORMAT) {
+			Ok(time) => time,
+			Err(_) => DateTime::<Local>::from(entry.metadata()?.modified()?).into(),
+		};
+		backups.push(BackupFile { name, path: entry.path(), time, size: 0 });
+	}
+	sort_newest_first(&mut backups);
+	Ok(backups)
+}
+
+fn sort_newest_first(backups: &mut [BackupFile]) {
+	backups.sort_by(|a, b| b.time.cmp(&a.time).then_with(|| b.name.cmp(&a.name)));
+}
+
+/// Decides which of the file_hashes backups the policy keeps,
+/// the backups must be sorted newest first, like list_backups returns them.
+///
+/// Returns whether each backup is kept, in the order of backups.
+pub(super) fn backups_to_keep(backups: &[BackupFile], policy: &RetentionPolicy) -> Vec<bool> {
+	let mut keep = vec![false; backups.len()];
+	keep.iter_mut().take(policy.keep_last).for_each(|keep| *keep = true);
+
+	// The first backup of a period is the newest backup of that period.
+	for (period_count, period_format) in [(policy.keep_daily, "%Y-%m-%d"), (policy.keep_weekly, "%G-W%V"), (policy.keep_monthly, "%Y-%m")] {
+		let mut periods = HashSet::new();
+		for (backup, keep) in backups.iter().zip(keep.iter_mut()) {
+			if periods.len() == period_count {
+				break;
+			}
+			if periods.insert(backup.time.format(period_format).to_string()) {
+				*keep = true;
+			}
+		}
+	}
+
+	if let Some(max_total_size) = policy.max_total_size {
+		let mut total_size = 0;
+		for (backup, keep) in backups.iter().zip(keep.iter_mut()).filter(|(_, keep)| **keep) {
+			total_size += backup.size;
+			if total_size > max_total_size {
+				*keep = false;
+			}
+		}
+	}
+	keep
+}
@@ -0,0 +1,493 @@
+/*
+	This file is part of file_hasher.
+
+	file_hasher is free software: you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation, either version 3 of the License, or
+	(at your option) any later version.
+
+	file_hasher is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License
+	along with file_hasher.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::io::{self, BufRead, Read, Write};
+
+use blake2::{
+	digest::{Update, VariableOutput},
+	Blake2bVar,
+};
+
+use super::{
+	e_d_element::{EDElement, EDVariantFields, ModifiedTime},
+	errors::*,
+	ignore_files::AcceptedIgnoreFiles,
+	list_format::{self, ListCompression, ListFormat, ListVersion, ReadList},
+};
+use crate::shared::{self, constants::*, Checksum, FileChecksum, HashAlgorithm};
+
+/// The first bytes of every binary list.
+pub(super) const BINARY_MAGIC: &[u8; 8] = b"FHBINLST";
+/// The version of the binary format, that is written after BINARY_MAGIC.
+const BINARY_FORMAT_VERSION: u8 = 2;
+/// The first version of the binary format, which has no accepted ignore files.
+const BINARY_FORMAT_VERSION_1: u8 = 1;
+
+// The kind byte that starts every record of a binary list.
+const END_RECORD: u8 = 0;
+const FILE_RECORD: u8 = 1;
+const FILE_WITHOUT_SIZE_RECORD: u8 = 2;
+const LINK_RECORD: u8 = 3;
+/// Stored instead of the nanoseconds of a modified time, that has no nanoseconds.
+const NO_NANOS: u32 = u32::MAX;
+
+// A binary list contains the same information as a text list of version 1.5,
+// and is protected by the same xor_checksum and final checksum.
+//
+// It starts with BINARY_MAGIC and BINARY_FORMAT_VERSION, followed by
+// the identifier of the hash algorithm, the xor_checksum and the
+// ignore_files_checksum, each prefixed by their length as a u8.
+// The header ends with the number of accepted ignore files as a u32,
+// followed by their paths, each prefixed by its length as a u32.
+// Version 1 of the binary format has no ignore_files_checksum and no ignore files,
+// and contains the same information as a text list of version 1.4.
+//
+// Every element is a record, starting with its kind, followed by its path
+// prefixed by its length as a little endian u32, the seconds of its modified
+// time as a u64, and the nanoseconds as a u32, or NO_NANOS.
+// File records continue with the raw checksum, whose length is the output length
+// of the hash algorithm, and file records with a size end with the size as a u64.
+// Link records end with the link target, prefixed by its length as a u32.
+//
+// The elements are followed by an END_RECORD and the final checksum,
+// prefixed by its length as a u8. Because the final checksum is stored last,
+// the list can be written and read in a single pass over the elements.
+
+/// Writes the elements, the xor_checksum, the hash_algorithm and the accepted_ignore_files as a binary list.
+pub(super) fn write_list(
+	writer: &mut dyn Write, element_list: &[EDElement], xor_checksum: &Checksum, hash_algorithm: HashAlgorithm,
+	accepted_ignore_files: &AcceptedIgnoreFiles,
+) -> io::Result<()> {
+	let mut hasher = Blake2bVar::new(HASH_OUTPUT_LENGTH).unwrap();
+
+	writer.write_all(BINARY_MAGIC)?;
+	writer.write_all(&[BINARY_FORMAT_VERSION])?;
+	write_short_bytes(writer, hash_algorithm.identifier().as_bytes())?;
+	write_short_bytes(writer, &**xor_checksum)?;
+	write_short_bytes(writer, &**accepted_ignore_files.get_checksum())?;
+	let ignore_file_count =
+		u32::try_from(accepted_ignore_files.get_paths().len()).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+	writer.write_all(&ignore_file_count.to_le_bytes())?;
+	for path in accepted_ignore_files.get_paths() {
+		write_long_bytes(writer, path.as_bytes())?;
+	}
+
+	for element in element_list {
+		let (kind, checksum, size, target) = match element.get_variant() {
+			EDVariantFields::File { checksum, size: Some(size) } => (FILE_RECORD, Some(checksum), Some(size), None),
+			EDVariantFields::File { checksum, size: None } => (FILE_WITHOUT_SIZE_RECORD, Some(checksum), None, None),
+			EDVariantFields::Link { target } => (LINK_RECORD, None, None, Some(target)),
+		};
+		writer.write_all(&[kind])?;
+		write_long_bytes(writer, element.get_path().as_bytes())?;
+		let modified_time = element.get_modified_time();
+		writer.write_all(&modified_time.get_secs().to_le_bytes())?;
+		writer.write_all(&modified_time.get_nanos().unwrap_or(NO_NANOS).to_le_bytes())?;
+		if let Some(checksum) = checksum {
+			writer.write_all(checksum)?;
+		}
+		if let Some(size) = size {
+			writer.write_all(&size.to_le_bytes())?;
+		}
+		if let Some(target) = target {
+			write_long_bytes(writer, target.as_bytes())?;
+		}
+		hasher.update(element.get_hash().as_ref());
+	}
+	hasher.update(xor_checksum.as_ref());
+	hasher.update(hash_algorithm.identifier().as_bytes());
+	accepted_ignore_files.update_hasher(&mut hasher);
+
+	writer.write_all(&[END_RECORD])?;
+	write_short_bytes(writer, &*shared::blake2_to_checksum(hasher))
+}
+
+/// Reads a binary list one element at a time, and validates its
+/// xor_checksum and final checksum, before returning it.
+pub(super) fn read_list(mut reader: impl BufRead, compression: ListCompression) -> Result<ReadList, EDListOpenError> {
+	let header_error = |err| EDListOpenError::BinaryFormatError("header".to_string(), err);
+	let mut magic = [0; BINARY_MAGIC.len()];
+	reader.read_exact(&mut magic).map_err(header_error)?;
+	let format_version = read_u8(&mut reader).map_err(header_error)?;
+	if magic != *BINARY_MAGIC || (format_version != BINARY_FORMAT_VERSION && format_version != BINARY_FORMAT_VERSION_1) {
+		Err(UnsupportedEDListVersion::Invalid(format!("binary {}", format_version)))?
+	}
+	let hash_algorithm = String::from_utf8(read_short_bytes(&mut reader).map_err(header_error)?)
+		.ok()
+		.and_then(|identifier| identifier.parse::<HashAlgorithm>().ok())
+		.ok_or(EDListOpenError::InvalidHashAlgorithm)?;
+	let mut file_xor_checksum = Checksum::default();
+	let xor_checksum_bytes = read_short_bytes(&mut reader).map_err(header_error)?;
+	if xor_checksum_bytes.len() != HASH_OUTPUT_LENGTH {
+		Err(EDListOpenError::InvalidXorChecksum)?
+	}
+	file_xor_checksum.copy_from_slice(&xor_checksum_bytes);
+	let (version, accepted_ignore_files) = if format_version == BINARY_FORMAT_VERSION_1 {
+		(ListVersion::V1_4, AcceptedIgnoreFiles::none())
+	}
+	else {
+		let mut ignore_files_checksum = Checksum::default();
+		let ignore_files_checksum_bytes = read_short_bytes(&mut reader).map_err(header_error)?;
+		if ignore_files_checksum_bytes.len() != HASH_OUTPUT_LENGTH {
+			Err(EDListOpenError::InvalidIgnoreFilesChecksum)?
+		}
+		ignore_files_checksum.copy_from_slice(&ignore_files_checksum_bytes);
+		let ignore_file_paths = read_strings(&mut reader).map_err(header_error)?;
+		(ListVersion::V1_5, AcceptedIgnoreFiles::new(ignore_file_paths, ignore_files_checksum))
+	};
+
+	let mut xor_checksum = Checksum::default();
+	let mut hasher = Blake2bVar::new(HASH_OUTPUT_LENGTH).unwrap();
+	let mut element_list = Vec::new();
+	while let Some(element) = read_element(&mut reader, hash_algorithm)
+		.map_err(|err| EDListOpenError::BinaryFormatError(format!("element {}", element_list.len() + 1), err))?
+	{
+		hasher.update(element.get_hash().as_ref());
+		xor_checksum ^= element.get_hash();
+		element_list.push(element);
+	}
+	let file_final_checksum =
+		read_short_bytes(&mut reader).map_err(|err| EDListOpenError::BinaryFormatError("final checksum".to_string(), err))?;
+	hasher.update(file_xor_checksum.as_ref());
+	hasher.update(hash_algorithm.identifier().as_bytes());
+	if version >= ListVersion::V1_5 {
+		accepted_ignore_files.update_hasher(&mut hasher);
+	}
+	let final_checksum = shared::blake2_to_checksum(hasher);
+
+	// Like for text lists, the ReadList object is created before comparing the checksums.
+	let read_list = ReadList {
+		version,
+		format: ListFormat::Binary,
+		hash_algorithm,
+		compression,
+		element_list,
+		xor_checksum: file_xor_checksum,
+		accepted_ignore_files,
+	};
+
+	// Verifying xor_checksum
+	if read_list.xor_checksum != xor_checksum {
+		Err(EDListOpenError::XorChecksumMismatch)?
+	}
+
+	// Verifying final_checksum.
+	if *file_final_checksum != *final_checksum {
+		Err(EDListOpenError::FinChecksumMismatch)?
+	}
+
+	Ok(read_list)
+}
+
+/// Formats as much as possible of a binary list as a text list,
+/// such that a damaged binary list can be shown to the user.
+pub(super) fn format_lossy(mut reader: impl BufRead) -> String {
+	let mut text = String::new();
+	let mut magic = [0; BINARY_MAGIC.len()];
+	let header = reader.read_exact(&mut magic).and_then(|_| read_u8(&mut reader)).and_then(|format_version| {
+		let identifier = read_short_bytes(&mut reader)?;
+		let xor_checksum = read_short_bytes(&mut reader)?;
+		let ignore_files = if format_version == BINARY_FORMAT_VERSION_1 {
+			None
+		}
+		else {
+			Some((read_short_bytes(&mut reader)?, read_strings(&mut reader)?))
+		};
+		Ok((String::from_utf8_lossy(&identifier).into_owned(), xor_checksum, ignore_files))
+	});
+	let (identifier, xor_checksum, ignore_files) = match header {
+		Ok(header) => header,
+		Err(_) => return text,
+	};
+	text.push_str(&format!("{}{}\n", HASH_ALGORITHM_PREFIX, identifier));
+	if let Some((ignore_files_checksum, ignore_file_paths)) = ignore_files {
+		text.push_str(&format!("{}{}\n", IGNORE_FILES_CHECKSUM_PREFIX, hex::encode_upper(ignore_files_checksum)));
+		text.push_str(&format!("{}{}\n", IGNORE_FILES_PREFIX, list_format::format_ignore_file_paths(&ignore_file_paths)));
+	}
+	text.push_str(&format!("{}{}\n", XOR_CHECKSUM_PREFIX, hex::encode_upper(xor_checksum)));
+
+	// Elements can only be read, if the hash algorithm is known.
+	if let Ok(hash_algorithm) = identifier.parse::<HashAlgorithm>() {
+		while let Ok(Some(element)) = read_element(&mut reader, hash_algorithm) {
+			text.push_str(&format!("{}\n", element));
+		}
+	}
+	text
+}
+
+/// Reads the next element of a binary list, returns None at the END_RECORD.
+fn read_element(reader: &mut impl BufRead, hash_algorithm: HashAlgorithm) -> io::Result<Option<EDElement>> {
+	let kind = read_u8(reader)?;
+	if kind == END_RECORD {
+		return Ok(None);
+	}
+	let path = read_string(reader)?;
+	let secs = u64::from_le_bytes(read_array(reader)?);
+	let nanos = match u32::from_le_bytes(read_array(reader)?) {
+		NO_NANOS => None,
+		nanos => Some(nanos),
+	};
+	let variant_fields = match kind {
+		FILE_RECORD | FILE_WITHOUT_SIZE_RECORD => {
+			let checksum = FileChecksum::from_slice(&read_bytes(reader, hash_algorithm.output_length() as u64)?);
+			let size = if kind == FILE_RECORD { Some(u64::from_le_bytes(read_array(reader)?)) } else { None };
+			EDVariantFields::File { checksum, size }
+		},
+		LINK_RECORD => EDVariantFields::Link { target: read_string(reader)? },
+		_ => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("invalid record kind {}", kind))),
+	};
+	Ok(Some(EDElement::from_internal(path, ModifiedTime::new(secs, nanos), variant_fields)))
+}
+
+fn read_u8(reader: &mut impl Read) -> io::Result<u8> {
+	Ok(read_array::<1>(reader)?[0])
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+	let mut array = [0; N];
+	reader.read_exact(&mut array)?;
+	Ok(array)
+}
+
+/// Reads bytes that are prefixed by their length as a u8.
+fn read_short_bytes(reader: &mut impl Read) -> io::Result<Vec<u8>> {
+	let length = read_u8(reader)?;
+	read_bytes(reader, length.into())
+}
+
+/// Reads a string that is prefixed by its length as a u32.
+fn read_string(reader: &mut impl Read) -> io::Result<String> {
+	let length = u32::from_le_bytes(read_array(reader)?);
+	String::from_utf8(read_bytes(reader, length.into())?).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Reads strings that are prefixed by their number as a u32.
+fn read_strings(reader: &mut impl Read) -> io::Result<Vec<String>> {
+	let count = u32::from_le_bytes(read_array(reader)?);
+	(0..count).map(|_| read_string(reader)).collect()
+}
+
+/// Reads length bytes, without allocating more memory than the reader contains,
+/// in case the length has been damaged.
+fn read_bytes(reader: &mut impl Read, length: u64) -> io::Result<Vec<u8>> {
+	let mut bytes = Vec::new();
+	reader.take(length).read_to_end(&mut bytes)?;
+	if bytes.len() as u64 != length {
+		return Err(io::ErrorKind::UnexpectedEof.into());
+	}
+	Ok(bytes)
+}
+
+fn write_short_bytes(writer: &mut dyn Write, bytes: &[u8]) -> io::Result<()> {
+	let length = u8::try_from(bytes.len()).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+	writer.write_all(&[length])?;
+	writer.write_all(bytes)
+}
+
+fn write_long_bytes(writer: &mut dyn Write, bytes: &[u8]) -> io::Result<()> {
+	let length = u32::try_from(bytes.len()).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+	writer.write_all(&length.to_le_bytes())?;
+	writer.write_all(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::e_d_list::list_format::{read_list, write_list};
+
+	/// Returns a file, a link and a file with an escaped path,
+	/// storing the fields that lists of the given version store.
+	fn elements(version: ListVersion) -> Vec<EDElement> {
+		let size = (version >= ListVersion::V1_2).then_some(1234);
+		let nanos = (version >= ListVersion::V1_3).then_some(5678);
+		let file = |path: &str, byte: u8| {
+			let checksum = FileChecksum::from_slice(&[byte; HASH_OUTPUT_LENGTH]);
+			EDElement::from_internal(path.to_string(), ModifiedTime::new(1_700_000_000, nanos), EDVariantFields::File { checksum, size })
+		};
+		vec![
+			file("./a/file", 0xAB),
+			EDElement::from_internal(
+				"./a/link".to_string(),
+				ModifiedTime::new(1_700_000_001, nanos),
+				EDVariantFields::Link { target: "file".to_string() },
+			),
+			file("./b/with, comma", 0x01),
+		]
+	}
+
+	const IGNORE_FILE_PATHS: [&str; 2] = ["./a/.file_hasher_ignore", r"./b/with, comma\/.file_hasher_ignore"];
+
+	/// Returns the accepted ignore files at IGNORE_FILE_PATHS,
+	/// if lists of the given version store them.
+	fn accepted_ignore_files(version: ListVersion) -> AcceptedIgnoreFiles {
+		if version < ListVersion::V1_5 {
+			return AcceptedIgnoreFiles::none();
+		}
+		let mut checksum = Checksum::default();
+		checksum.fill(0xCD);
+		AcceptedIgnoreFiles::new(IGNORE_FILE_PATHS.map(String::from).to_vec(), checksum)
+	}
+
+	/// Formats the elements as a text list of the given version, like the versions of file_hasher
+	/// that wrote it, hashed using BLAKE2b-256 and with the accepted_ignore_files of the version.
+	fn text_list(version: ListVersion, element_list: &[EDElement]) -> Vec<u8> {
+		let hash_algorithm = HashAlgorithm::Blake2b256;
+		let accepted_ignore_files = accepted_ignore_files(version);
+		let mut xor_checksum = Checksum::default();
+		let mut hasher = Blake2bVar::new(HASH_OUTPUT_LENGTH).unwrap();
+		for element in element_list {
+			xor_checksum ^= element.get_hash();
+			hasher.update(element.get_hash().as_ref());
+		}
+		hasher.update(xor_checksum.as_ref());
+
+		let mut text = format!("{}{}\n", LIST_VERSION_PREFIX, version);
+		if version >= ListVersion::V1_4 {
+			hasher.update(hash_algorithm.identifier().as_bytes());
+			text.push_str(&format!("{}{}\n", HASH_ALGORITHM_PREFIX, hash_algorithm));
+		}
+		if version >= ListVersion::V1_5 {
+			accepted_ignore_files.update_hasher(&mut hasher);
+			text.push_str(&format!("{}{}\n", IGNORE_FILES_CHECKSUM_PREFIX, accepted_ignore_files.get_checksum()));
+			let ignore_file_paths = r"./a/.file_hasher_ignore,./b/with\, comma\\/.file_hasher_ignore";
+			text.push_str(&format!("{}{}\n", IGNORE_FILES_PREFIX, ignore_file_paths));
+		}
+		text.push_str(&format!("{}{}\n", XOR_CHECKSUM_PREFIX, xor_checksum));
+		text.push_str(&format!("{}{}\n", FIN_CHECKSUM_PREFIX, shared::blake2_to_checksum(hasher)));
+		element_list.iter().for_each(|element| text.push_str(&format!("{}\n", element)));
+		text.into_bytes()
+	}
+
+	fn read(contents: &[u8]) -> Result<ReadList, EDListOpenError> {
+		read_list(contents)
+	}
+
+	fn write(read_list: &ReadList, format: ListFormat, compression: ListCompression) -> Vec<u8> {
+		let mut contents = Vec::new();
+		write_list(
+			&mut contents,
+			&read_list.element_list,
+			&read_list.xor_checksum,
+			read_list.hash_algorithm,
+			&read_list.accepted_ignore_files,
+			format,
+			compression,
+		)
+		.unwrap();
+		contents
+	}
+
+	fn assert_same_list(expected: &ReadList, actual: &ReadList) {
+		let to_strings = |read_list: &ReadList| read_list.element_list.iter().map(EDElement::to_string).collect::<Vec<_>>();
+		assert_eq!(to_strings(expected), to_strings(actual));
+		assert_eq!(expected.xor_checksum, actual.xor_checksum);
+		assert_eq!(expected.hash_algorithm, actual.hash_algorithm);
+		assert_eq!(expected.accepted_ignore_files, actual.accepted_ignore_files);
+	}
+
+	/// Returns a valid binary list of the current version.
+	fn binary_list() -> Vec<u8> {
+		let read_list = read(&text_list(ListVersion::V1_5, &elements(ListVersion::V1_5))).unwrap();
+		write(&read_list, ListFormat::Binary, ListCompression::None)
+	}
+
+	/// Returns the position of the xor_checksum of binary_list.
+	fn xor_checksum_position() -> usize {
+		BINARY_MAGIC.len() + 1 + (1 + HashAlgorithm::Blake2b256.identifier().len()) + 1
+	}
+
+	/// Returns the position of the first record of binary_list.
+	fn first_record_position() -> usize {
+		let ignore_file_paths_length: usize = IGNORE_FILE_PATHS.iter().map(|path| 4 + path.len()).sum();
+		xor_checksum_position() + HASH_OUTPUT_LENGTH + (1 + HASH_OUTPUT_LENGTH) + 4 + ignore_file_paths_length
+	}
+
+	fn assert_binary_format_error(result: Result<ReadList, EDListOpenError>, expected_part: &str) {
+		match result {
+			Err(EDListOpenError::BinaryFormatError(part, err)) => {
+				assert_eq!(part, expected_part);
+				assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+			},
+			Err(err) => panic!("expected a BinaryFormatError, got {}", err),
+			Ok(_) => panic!("expected a BinaryFormatError, got a valid list"),
+		}
+	}
+
+	#[test]
+	fn every_version_round_trips_through_every_format_and_compression() {
+		let conversions = [
+			(ListFormat::Binary, ListCompression::None),
+			(ListFormat::Binary, ListCompression::Gzip),
+			(ListFormat::Text, ListCompression::Gzip),
+			(ListFormat::Text, ListCompression::None),
+		];
+		for version in [ListVersion::V1_1, ListVersion::V1_2, ListVersion::V1_3, ListVersion::V1_4, ListVersion::V1_5] {
+			let contents = text_list(version, &elements(version));
+			let original = read(&contents).unwrap();
+			assert_eq!(original.version, version);
+			assert_eq!(original.format, ListFormat::Text);
+			assert_eq!(original.accepted_ignore_files, accepted_ignore_files(version));
+
+			let mut previous = read(&contents).unwrap();
+			for (format, compression) in conversions {
+				let converted = read(&write(&previous, format, compression)).unwrap();
+				assert_eq!(converted.version, ListVersion::current());
+				assert_eq!(converted.format, format);
+				assert_eq!(converted.compression, compression);
+				assert_same_list(&original, &converted);
+				previous = converted;
+			}
+		}
+	}
+
+	#[test]
+	fn truncated_record_is_rejected() {
+		let mut contents = binary_list();
+		contents.truncate(first_record_position() + 10);
+		assert_binary_format_error(read(&contents), "element 1");
+	}
+
+	#[test]
+	fn oversized_length_prefix_is_rejected() {
+		let mut contents = binary_list();
+		// The length of the path follows the kind byte of the record.
+		let length_position = first_record_position() + 1;
+		contents[length_position..length_position + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+		assert_binary_format_error(read(&contents), "element 1");
+	}
+
+	#[test]
+	fn flipped_final_checksum_byte_is_rejected() {
+		let mut contents = binary_list();
+		*contents.last_mut().unwrap() ^= 1;
+		assert!(matches!(read(&contents), Err(EDListOpenError::FinChecksumMismatch)));
+	}
+
+	#[test]
+	fn flipped_xor_checksum_byte_is_rejected() {
+		let mut contents = binary_list();
+		contents[xor_checksum_position()] ^= 1;
+		assert!(matches!(read(&contents), Err(EDListOpenError::XorChecksumMismatch)));
+	}
+
+	#[test]
+	fn flipped_element_checksum_byte_is_rejected() {
+		let contents = String::from_utf8(text_list(ListVersion::V1_5, &elements(ListVersion::V1_5))).unwrap();
+		let contents = contents.replacen("file(ABAB", "file(BBAB", 1);
+		assert!(matches!(read(contents.as_bytes()), Err(EDListOpenError::XorChecksumMismatch)));
+	}
+}
@@ -21,27 +21,141 @@ use blake2::{
 	digest::{Update, VariableOutput},
 	Blake2bVar,
 };
-use hex::decode_to_slice;
-
 use crate::{
 	shared,
-	shared::{constants::HASH_OUTPUT_LENGTH, Checksum},
+	shared::{constants::HASH_OUTPUT_LENGTH, Checksum, FileChecksum, HashAlgorithm},
 };
 
 pub mod errors;
 use errors::*;
 
+/// The size of the buffer that files are read into while they are hashed.
+const HASH_BUFFER_SIZE: usize = 1024 * 1024;
+
+thread_local! {
+	/// Every thread hashing files reuses its own buffer, instead of allocating one for every file.
+	static HASH_BUFFER: std::cell::RefCell<Vec<u8>> = std::cell::RefCell::new(vec![0u8; HASH_BUFFER_SIZE]);
+}
+
 /// EDVariantFields is used to manage whether we are storing
 /// a file or a symbolic link.
+///
+/// The size of a file is None, if the element was created
+/// by a version of file_hasher that did not store file sizes.
 #[derive(Debug, PartialEq, Eq, std::hash::Hash, Clone)]
 pub enum EDVariantFields {
-	File { checksum: Checksum },
+	File { checksum: FileChecksum, size: Option<u64> },
 	Link { target: String },
 }
 impl EDVariantFields {
 	pub fn is_link(&self) -> bool {
 		if let EDVariantFields::Link { target: _ } = self { true } else { false }
 	}
+
+	/// Returns the size of a file, or 0 for links and files without a stored size.
+	pub fn size(&self) -> u64 {
+		if let EDVariantFields::File { checksum: _, size: Some(size) } = self { *size } else { 0 }
+	}
+
+	/// Returns true if both variants describe the same file content,
+	/// or the same link target.
+	/// Unlike comparing with ==, a missing file size is not
+	/// considered to be different from a known file size.
+	pub fn has_same_content(&self, other: &EDVariantFields) -> bool {
+		use EDVariantFields::*;
+		match (self, other) {
+			(File { checksum, size }, File { checksum: other_checksum, size: other_size }) => {
+				checksum == other_checksum && (size.is_none() || other_size.is_none() || size == other_size)
+			},
+			(Link { target }, Link { target: other_target }) => target == other_target,
+			_ => false,
+		}
+	}
+}
+impl std::fmt::Display for EDVariantFields {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			EDVariantFields::File { checksum, size: Some(size) } => write!(f, "file with checksum {} and size {} bytes", checksum, size),
+			EDVariantFields::File { checksum, size: None } => write!(f, "file with checksum {}", checksum),
+			EDVariantFields::Link { target } => write!(f, "link with target {}", target),
+		}
+	}
+}
+
+/// ModifiedTime stores the time of the last modification
+/// of a file or link, as time since epoch.
+///
+/// nanos is None, if the element was created by a version
+/// of file_hasher that only stored whole seconds.
+/// In that case only the seconds are compared.
+#[derive(Debug, PartialEq, Eq, std::hash::Hash, Copy, Clone)]
+pub struct ModifiedTime {
+	secs:  u64,
+	nanos: Option<u32>,
+}
+impl ModifiedTime {
+	/// Reads the modified time from the given metadata.
+	///
+	/// Panics if the filesystem/OS doesn't support reading
+	/// the last modified time of a file, or interpreting
+	/// it as time since epoch.
+	pub fn from_metadata(metadata: &fs::Metadata) -> ModifiedTime {
+		let duration = metadata.modified().unwrap().duration_since(SystemTime::UNIX_EPOCH).unwrap();
+		ModifiedTime { secs: duration.as_secs(), nanos: Some(duration.subsec_nanos()) }
+	}
+
+	pub(crate) fn new(secs: u64, nanos: Option<u32>) -> ModifiedTime {
+		ModifiedTime { secs, nanos }
+	}
+
+	/// Returns true if the two times are identical, comparing
+	/// at nanosecond precision, if both times have it.
+	pub fn matches(&self, other: &ModifiedTime) -> bool {
+		self.secs == other.secs && (self.nanos.is_none() || other.nanos.is_none() || self.nanos == other.nanos)
+	}
+
+	pub fn get_secs(&self) -> u64 {
+		self.secs
+	}
+
+	pub fn get_nanos(&self) -> Option<u32> {
+		self.nanos
+	}
+
+	/// Updates the hasher with the modified time.
+	/// Times without nanoseconds are hashed like before nanoseconds
+	/// were stored, so that lists from earlier versions can still be validated.
+	pub(crate) fn update_hasher(&self, hasher: &mut Blake2bVar) {
+		hasher.update(&self.secs.to_le_bytes());
+		if let Some(nanos) = self.nanos {
+			hasher.update(&nanos.to_le_bytes());
+		}
+	}
+}
+impl std::str::FromStr for ModifiedTime {
+	type Err = EDElementParseError;
+
+	/// Parses a modified time in the format "secs" or "secs.nanos",
+	/// where nanos always has 9 digits.
+	fn from_str(time_string: &str) -> Result<ModifiedTime, EDElementParseError> {
+		match time_string.split_once('.') {
+			Some((secs, nanos)) => {
+				if nanos.len() != 9 {
+					return Err(EDElementParseError::InvalidNanoseconds);
+				}
+				Ok(ModifiedTime { secs: secs.parse()?, nanos: Some(nanos.parse()?) })
+			},
+			None => Ok(ModifiedTime { secs: time_string.parse()?, nanos: None }),
+		}
+	}
+}
+impl std::fmt::Display for ModifiedTime {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self.nanos {
+			Some(nanos) => write!(f, "{}.{:09}", self.secs, nanos),
+			None => write!(f, "{}", self.secs),
+		}
+	}
 }
 
 /// EDElement, a shorthand for Error-detect-element
@@ -63,14 +177,14 @@ impl EDVariantFields {
 #[derive(Debug, Clone)]
 pub struct EDElement {
 	path:           String,
-	modified_time:  u64,
+	modified_time:  ModifiedTime,
 	variant_fields: EDVariantFields,
 	element_hash:   Checksum,
 }
 impl EDElement {
 	/// from_internal creates an EDElement from the given arguments
 	/// while also creating the element_hash for the EDElement.
-	fn from_internal(path: String, modified_time: u64, variant_fields: EDVariantFields) -> EDElement {
+	pub(super) fn from_internal(path: String, modified_time: ModifiedTime, variant_fields: EDVariantFields) -> EDElement {
 		let mut new_element = EDElement { path, modified_time, variant_fields, element_hash: Checksum::default() };
 		new_element.calculate_hash();
 		new_element
@@ -79,9 +193,16 @@ impl EDElement {
 	fn calculate_hash(&mut self) {
 		let mut hasher = Blake2bVar::new(HASH_OUTPUT_LENGTH).unwrap();
 		hasher.update(self.path.as_bytes());
-		hasher.update(&self.modified_time.to_le_bytes());
+		self.modified_time.update_hasher(&mut hasher);
 		match &self.variant_fields {
-			EDVariantFields::File { checksum } => hasher.update(checksum.as_ref()),
+			EDVariantFields::File { checksum, size } => {
+				hasher.update(checksum.as_ref());
+				// Elements without a size are hashed like before sizes were stored,
+				// so that lists from earlier versions can still be validated.
+				if let Some(size) = size {
+					hasher.update(&size.to_le_bytes());
+				}
+			},
 			EDVariantFields::Link { target } => hasher.update(target.as_bytes()),
 		}
 		self.element_hash = shared::blake2_to_checksum(hasher);
@@ -90,6 +211,7 @@ impl EDElement {
 	/// from_path generates an EDElement from a path.
 	/// It detects automatically whether the path
 	/// refers to a link or a file.
+	/// Files are hashed using hash_algorithm.
 	///
 	/// Returns an error if the path refers to a directory.
 	/// Or if in some other way processing of the file does
@@ -102,15 +224,16 @@ impl EDElement {
 	/// * The filesystem/OS doesn't support reading the link_path of a symbolic link.
 	/// * The filesystem doesn't support reading the modified time of a file.
 	/// * The argument "path" is neither a file nor a symbolic link.
-	pub fn from_path(path: String) -> Result<EDElement, EDElementError> {
+	pub fn from_path(path: String, hash_algorithm: HashAlgorithm) -> Result<EDElement, EDElementError> {
 		let metadata = fs::symlink_metadata(&path).map_err(|err| EDElementError::GetMetaDataError(path.to_string(), err))?;
-		let modified_time = metadata.modified().unwrap().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+		let modified_time = ModifiedTime::from_metadata(&metadata);
 
 		if metadata.is_file() {
 			// The path is a file.
 			let mut file = File::open(&path).map_err(|err| EDElementError::OpenFileError(path.to_string(), err))?;
-			let checksum = EDElement::hash_file(&mut file).map_err(|err| EDElementError::FileHashingError(path.to_string(), err))?;
-			let file_fields = EDVariantFields::File { checksum };
+			let checksum =
+				EDElement::hash_file(&mut file, hash_algorithm).map_err(|err| EDElementError::FileHashingError(path.to_string(), err))?;
+			let file_fields = EDVariantFields::File { checksum, size: Some(metadata.len()) };
 			Ok(EDElement::from_internal(path, modified_time, file_fields))
 		}
 		else {
@@ -127,8 +250,49 @@ impl EDElement {
 		}
 	}
 
+	/// Creates a file element from a checksum that was calculated elsewhere,
+	/// such as a checksum from an imported manifest.
+	/// The modified time and the size are read from metadata.
+	///
+	/// Panics if the filesystem doesn't support reading the modified time of a file.
+	pub(crate) fn from_checksum(path: String, metadata: &fs::Metadata, checksum: FileChecksum) -> EDElement {
+		let file_fields = EDVariantFields::File { checksum, size: Some(metadata.len()) };
+		EDElement::from_internal(path, ModifiedTime::from_metadata(metadata), file_fields)
+	}
+
+	/// Returns a copy of the element, with the size of the file and the nanoseconds
+	/// of the modified time read from metadata, if they weren't stored by the
+	/// version of file_hasher that created the element.
+	///
+	/// Returns None if nothing is missing, or if the modified time in whole seconds,
+	/// or the type of the element, doesn't match metadata, since the file may have changed.
+	///
+	/// Panics if the filesystem doesn't support reading the modified time of a file.
+	pub(crate) fn with_missing_metadata(&self, metadata: &fs::Metadata) -> Option<EDElement> {
+		let modified_time = ModifiedTime::from_metadata(metadata);
+		if modified_time.secs != self.modified_time.secs || metadata.is_file() == self.variant_fields.is_link() {
+			return None;
+		}
+		let variant_fields = match &self.variant_fields {
+			EDVariantFields::File { checksum, size: None } => {
+				EDVariantFields::File { checksum: checksum.clone(), size: Some(metadata.len()) }
+			},
+			variant_fields => variant_fields.clone(),
+		};
+		let modified_time = ModifiedTime { secs: self.modified_time.secs, nanos: self.modified_time.nanos.or(modified_time.nanos) };
+		let element = EDElement::from_internal(self.path.clone(), modified_time, variant_fields);
+		(element.element_hash != self.element_hash).then_some(element)
+	}
+
+	/// Returns true if the element stores the nanoseconds of its modified time,
+	/// and the size of the file if it is a file, which elements created by old versions of file_hasher don't.
+	pub(crate) fn has_all_metadata(&self) -> bool {
+		self.modified_time.nanos.is_some() && !matches!(self.variant_fields, EDVariantFields::File { size: None, .. })
+	}
+
 	/// Does a cursory test for if the path has been deleted,
-	/// or if the modified time of the path has been changed.
+	/// if the modified time of the path has been changed,
+	/// or if the size of a file has been changed.
 	///
 	/// If the metadata does not match the stored metadata, a
 	/// Err<String> is returned.
@@ -142,17 +306,22 @@ impl EDElement {
 		if metadata.is_dir() {
 			Err(EDElementVerifyError::PathIsDirectory(self.path.to_owned()))?
 		}
-		let modified_time = metadata.modified().unwrap().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
-		if modified_time != self.modified_time {
+		let modified_time = ModifiedTime::from_metadata(&metadata);
+		if !modified_time.matches(&self.modified_time) {
 			Err(EDElementVerifyError::TimeChanged(self.path.to_owned()))?
 		}
-		else {
-			Ok(())
+		match self.variant_fields {
+			EDVariantFields::File { checksum: _, size: Some(size) } if metadata.is_file() && metadata.len() != size => {
+				Err(EDElementVerifyError::SizeChanged(self.path.to_owned()))?
+			},
+			_ => Ok(()),
 		}
 	}
 
 	/// test_integrity tests the integrity of the EDElement against
 	/// the file or symbolic link it points to.
+	/// Files are hashed using hash_algorithm, which must be the
+	/// algorithm the element was created with.
 	///
 	/// If the symbolic_link or file has changed, or there has
 	/// been corruption in the EDElement struct, an Err
@@ -167,19 +336,19 @@ impl EDElement {
 	///
 	/// The filesystem/OS doesn't support reading
 	/// the link_path of a symbolic link
-	pub fn test_integrity(&self) -> Result<(), EDElementError> {
+	pub fn test_integrity(&self, hash_algorithm: HashAlgorithm) -> Result<(), EDElementError> {
 		let metadata = fs::symlink_metadata(&self.path).map_err(|err| EDElementError::GetMetaDataError(self.path.to_owned(), err))?;
 
 		let time_changed = {
-			let modified_time = metadata.modified().unwrap().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
-			modified_time != self.modified_time
+			let modified_time = ModifiedTime::from_metadata(&metadata);
+			!modified_time.matches(&self.modified_time)
 		};
 
 		match &self.variant_fields {
-			EDVariantFields::File { checksum } => {
+			EDVariantFields::File { checksum, size: _ } => {
 				let mut file = File::open(&self.path).map_err(|err| EDElementError::OpenFileError(self.path.to_owned(), err))?;
-				let file_hash =
-					EDElement::hash_file(&mut file).map_err(|err| EDElementError::FileHashingError(self.path.to_owned(), err))?;
+				let file_hash = EDElement::hash_file(&mut file, hash_algorithm)
+					.map_err(|err| EDElementError::FileHashingError(self.path.to_owned(), err))?;
 				if file_hash == *checksum {
 					if time_changed {
 						Err(EDElementVerifyError::TimeChangedButFileCorrectError(self.path.to_owned()))?
@@ -238,22 +407,27 @@ impl EDElement {
 		}
 	}
 
-	/// hash_file reads a file, and creates a hash for it in an
-	/// u8 vector, of length HASH_OUTPUT_LENGTH.
+	/// hash_file reads a file, and creates a hash for it using
+	/// hash_algorithm, with the output length of hash_algorithm.
 	/// If there is trouble reading the file, we will return
 	/// the error given.
-	pub fn hash_file(file: &mut dyn Read) -> Result<Checksum, FileHashingError> {
-		let buffer_size = 40 * 1024 * 1024; // Buffer_size = 40MB
-		let mut buffer = vec![0u8; buffer_size];
-		let mut hasher = Blake2bVar::new(HASH_OUTPUT_LENGTH).unwrap();
-		loop {
-			let result_size = file.read(&mut buffer)?;
-			hasher.update(&buffer[0..result_size]);
-			if result_size != buffer_size {
-				break;
+	///
+	/// The file is read into the HASH_BUFFER of the current thread, until the end of the file.
+	pub fn hash_file(file: &mut dyn Read, hash_algorithm: HashAlgorithm) -> Result<FileChecksum, FileHashingError> {
+		HASH_BUFFER.with(|buffer| {
+			let mut buffer = buffer.borrow_mut();
+			let mut hasher = hash_algorithm.hasher();
+			loop {
+				let result_size = match file.read(&mut buffer) {
+					Ok(0) => break,
+					Ok(result_size) => result_size,
+					Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+					Err(err) => return Err(err.into()),
+				};
+				hasher.update(&buffer[0..result_size]);
 			}
-		}
-		Ok(shared::blake2_to_checksum(hasher))
+			Ok(hasher.finalize())
+		})
 	}
 
 	/// Returns a hash of the entire EDElement.
@@ -283,7 +457,7 @@ impl EDElement {
 		self.calculate_hash();
 	}
 
-	pub fn get_modified_time(&self) -> u64 {
+	pub fn get_modified_time(&self) -> ModifiedTime {
 		self.modified_time
 	}
 
@@ -335,7 +509,7 @@ impl std::convert::TryFrom<&str> for EDElement {
 					None => return Err(EDElementParseError::NoModifiedTimeTerminator),
 				}
 			}
-			time_string.parse::<u64>()?
+			time_string.parse::<ModifiedTime>()?
 		};
 
 		// Parse the variant data of the EDElement.
@@ -344,18 +518,32 @@ impl std::convert::TryFrom<&str> for EDElement {
 		};
 		let variant_fields = match &char_iterator.as_str().as_bytes()[0..5] {
 			b"file(" => {
-				let mut file_checksum = Checksum::default();
-				if char_iterator.as_str().len() < 5 + (HASH_OUTPUT_LENGTH * 2) {
+				// The length of the checksum depends on the hash algorithm of the list.
+				let checksum_string = &char_iterator.as_str()[5..];
+				let checksum_length = checksum_string.find([',', ')']).ok_or(EDElementParseError::NoVariantTerminator)?;
+				if checksum_length == 0 {
 					return Err(EDElementParseError::IncompleteFileHash);
 				}
-				decode_to_slice(&char_iterator.as_str().as_bytes()[5..5 + HASH_OUTPUT_LENGTH * 2], &mut *file_checksum)?;
-				char_iterator = char_iterator.as_str()[5 + HASH_OUTPUT_LENGTH * 2..].chars();
-
-				match char_iterator.next() {
-					Some(')') => (),
+				let file_checksum = FileChecksum::from_hex(&checksum_string[..checksum_length])?;
+				char_iterator = checksum_string[checksum_length..].chars();
+
+				// The file size is optional, since it was not stored before list version 1.2.
+				let size = match char_iterator.next() {
+					Some(')') => None,
+					Some(',') => {
+						let mut size_string = String::new();
+						loop {
+							match char_iterator.next() {
+								Some(')') => break,
+								Some(character) => size_string.push(character),
+								None => return Err(EDElementParseError::NoVariantTerminator),
+							}
+						}
+						Some(size_string.parse::<u64>().map_err(EDElementParseError::FileSizeCouldNotBeParsed)?)
+					},
 					_ => return Err(EDElementParseError::NoVariantTerminator),
-				}
-				EDVariantFields::File { checksum: file_checksum }
+				};
+				EDVariantFields::File { checksum: file_checksum, size }
 			},
 			b"link(" => {
 				char_iterator = char_iterator.as_str()[5..].chars();
@@ -389,7 +577,8 @@ impl std::convert::TryFrom<&str> for EDElement {
 impl std::fmt::Display for EDElement {
 	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
 		let variant_fields = match &self.variant_fields {
-			EDVariantFields::File { checksum } => format!("file({})", hex::encode_upper(checksum.as_ref())),
+			EDVariantFields::File { checksum, size: Some(size) } => format!("file({},{})", hex::encode_upper(checksum.as_ref()), size),
+			EDVariantFields::File { checksum, size: None } => format!("file({})", hex::encode_upper(checksum.as_ref())),
 			EDVariantFields::Link { target } => format!("link({})", target.replace(r"\", r"\\").replace(")", r"\)")),
 		};
 		write!(
@@ -25,6 +25,35 @@ impl fmt::Display for EDElementError {
 		}
 	}
 }
+impl EDElementError {
+	/// Returns the name of the variant, like "OpenFileError", or the kind of the wrapped error.
+	pub fn kind(&self) -> &'static str {
+		use EDElementError::*;
+		match self {
+			GetMetaDataError(_, _) => "GetMetaDataError",
+			OpenFileError(_, _) => "OpenFileError",
+			FileHashingError(_, _) => "FileHashingError",
+			InvalidUtf8Link(_) => "InvalidUtf8Link",
+			VerifyLinkPathError(err) => err.kind(),
+			VerifyError(err) => err.kind(),
+			LinkTargetInvalidUtf8(_) => "LinkTargetInvalidUtf8",
+		}
+	}
+
+	/// Returns the path of the element that the error occurred on.
+	pub fn path(&self) -> &str {
+		use EDElementError::*;
+		match self {
+			GetMetaDataError(path, _) |
+			OpenFileError(path, _) |
+			FileHashingError(path, _) |
+			InvalidUtf8Link(path) |
+			LinkTargetInvalidUtf8(path) => path,
+			VerifyLinkPathError(err) => err.path(),
+			VerifyError(err) => err.path(),
+		}
+	}
+}
 impl From<VerifyLinkPathError> for EDElementError {
 	fn from(err: VerifyLinkPathError) -> EDElementError {
 		EDElementError::VerifyLinkPathError(err)
@@ -72,6 +101,25 @@ impl fmt::Display for VerifyLinkPathError {
 	}
 }
 
+impl VerifyLinkPathError {
+	/// Returns "LinkFileNoParentError" or "UnableToOpenLinkTarget".
+	pub fn kind(&self) -> &'static str {
+		use VerifyLinkPathError::*;
+		match self {
+			LinkFileNoParentError(_, _) => "LinkFileNoParentError",
+			UnableToOpenLinkTarget(_, _, _) => "UnableToOpenLinkTarget",
+		}
+	}
+
+	/// Returns the path of the link that the error occurred on.
+	pub fn path(&self) -> &str {
+		use VerifyLinkPathError::*;
+		match self {
+			LinkFileNoParentError(path, _) | UnableToOpenLinkTarget(path, _, _) => path,
+		}
+	}
+}
+
 #[derive(Debug)]
 pub enum EDElementVerifyError {
 	TimeChangedButFileCorrectError(String),
@@ -82,6 +130,7 @@ pub enum EDElementVerifyError {
 	LinkTargetInvalidTimeChanged(String),
 	PathIsDirectory(String),
 	TimeChanged(String),
+	SizeChanged(String),
 }
 impl Error for EDElementVerifyError {}
 impl fmt::Display for EDElementVerifyError {
@@ -98,6 +147,41 @@ impl fmt::Display for EDElementVerifyError {
 			},
 			PathIsDirectory(path) => write!(f, "Path \"{}\" is a directory", path),
 			TimeChanged(path) => write!(f, "File with path \"{}\", has a different modified time than expected", path),
+			SizeChanged(path) => write!(f, "File with path \"{}\", has a different size than expected", path),
+		}
+	}
+}
+
+impl EDElementVerifyError {
+	/// Returns the name of the variant, like "InvalidChecksum" or "TimeChanged".
+	pub fn kind(&self) -> &'static str {
+		use EDElementVerifyError::*;
+		match self {
+			TimeChangedButFileCorrectError(_) => "TimeChangedButFileCorrectError",
+			TimeChangedAndFileChanged(_) => "TimeChangedAndFileChanged",
+			InvalidChecksum(_) => "InvalidChecksum",
+			LinkTargetValidTimeChanged(_) => "LinkTargetValidTimeChanged",
+			LinkTargetInvalid(_) => "LinkTargetInvalid",
+			LinkTargetInvalidTimeChanged(_) => "LinkTargetInvalidTimeChanged",
+			PathIsDirectory(_) => "PathIsDirectory",
+			TimeChanged(_) => "TimeChanged",
+			SizeChanged(_) => "SizeChanged",
+		}
+	}
+
+	/// Returns the path of the element that the error occurred on.
+	pub fn path(&self) -> &str {
+		use EDElementVerifyError::*;
+		match self {
+			TimeChangedButFileCorrectError(path) |
+			TimeChangedAndFileChanged(path) |
+			InvalidChecksum(path) |
+			LinkTargetValidTimeChanged(path) |
+			LinkTargetInvalid(path) |
+			LinkTargetInvalidTimeChanged(path) |
+			PathIsDirectory(path) |
+			TimeChanged(path) |
+			SizeChanged(path) => path,
 		}
 	}
 }
@@ -109,9 +193,11 @@ pub enum EDElementParseError {
 	NoFilePathTerminator,
 	NoModifiedTimeTerminator,
 	ModifiedTimeCouldNotBeParsed(std::num::ParseIntError),
+	InvalidNanoseconds,
 	NoVariantInformation,
 	IncompleteFileHash,
 	FileHashDecodeError(hex::FromHexError),
+	FileSizeCouldNotBeParsed(std::num::ParseIntError),
 	NoVariantTerminator,
 	InvalidVariantIdentifier,
 	NoTerminatorBracket,
@@ -126,9 +212,11 @@ impl fmt::Display for EDElementParseError {
 			NoFilePathTerminator => write!(f, "Missing terminating character after path name"),
 			NoModifiedTimeTerminator => write!(f, "Modified time string has no terminator character"),
 			ModifiedTimeCouldNotBeParsed(err) => write!(f, "Modified time couldn't be parsed, err = {}", err),
+			InvalidNanoseconds => write!(f, "Nanoseconds of modified time must have exactly 9 digits"),
 			NoVariantInformation => write!(f, "EDElement is missing information about its variant type"),
 			IncompleteFileHash => write!(f, "File_hash is incomplete"),
 			FileHashDecodeError(err) => write!(f, "Error decoding file hash: {}", err),
+			FileSizeCouldNotBeParsed(err) => write!(f, "File size couldn't be parsed, err = {}", err),
 			NoVariantTerminator => write!(f, "Missing terminating ')' character after file_hash, or link_target"),
 			InvalidVariantIdentifier => write!(f, "Invalid variant identifier in EDElement string"),
 			NoTerminatorBracket => write!(f, "Missing EDElement terminator bracket"),
@@ -7,13 +7,21 @@ pub enum EDListOpenError {
 	IoError(std::io::Error),
 	ChecksumsMissingError,
 	UnsupportedEDListVersion(UnsupportedEDListVersion),
+	InvalidHashAlgorithm,
 	InvalidXorChecksum,
+	InvalidIgnoreFilesChecksum,
+	InvalidIgnoreFiles,
 	UndecodableXorChecksum(hex::FromHexError),
 	InvalidFinChecksum,
+	DecompressionError(std::io::Error),
+	BinaryFormatError(String, std::io::Error),
 	EDElementParseError(e_d_element::errors::EDElementParseError, usize),
+	ChecksumLengthMismatch(usize),
 	XorChecksumMismatch,
 	FinChecksumMismatch,
 	WriteBackupError(WriteBackupError),
+	RestoreBackupError(std::io::Error),
+	IgnoreFilesError(IndexError),
 }
 impl std::error::Error for EDListOpenError {}
 impl std::fmt::Display for EDListOpenError {
@@ -22,18 +30,51 @@ impl std::fmt::Display for EDListOpenError {
 		match self {
 			CouldNotOpenFileHashesFile => write!(f, "file_hashes file could not be opened, or created"),
 			IoError(err) => write!(f, "There was a problem reading from file, err = {}", err),
-			ChecksumsMissingError => write!(f, "Missing first three lines with metadata about file_hashes"),
+			ChecksumsMissingError => write!(f, "Missing the lines with metadata about file_hashes"),
 			UnsupportedEDListVersion(err) => write!(f, "{}", err),
-			InvalidXorChecksum => write!(f, "Invalid xor_checksum_string at line 2 of file_hashes"),
+			InvalidHashAlgorithm => write!(f, "Invalid or unknown hash algorithm at line 2 of file_hashes"),
+			InvalidXorChecksum => write!(f, "Invalid xor_checksum_string in file_hashes"),
+			InvalidIgnoreFilesChecksum => write!(f, "Invalid ignore_files_checksum_string in file_hashes"),
+			InvalidIgnoreFiles => write!(f, "Invalid ignore_files_string in file_hashes"),
 			UndecodableXorChecksum(err) => write!(f, "error decoding xor_checksum to u8 array, err = {}", err),
-			InvalidFinChecksum => write!(f, "Invalid fin_checksum_string at line 3 of file_hashes"),
-			EDElementParseError(err, i) => write!(f, "Error interpreting EDElement from file_hashes, linecount = {}, err = {}", i + 4, err),
+			InvalidFinChecksum => write!(f, "Invalid fin_checksum_string in file_hashes"),
+			DecompressionError(err) => write!(f, "Error decompressing file_hashes, err = {}", err),
+			BinaryFormatError(part, err) => write!(f, "Error reading the {} of the binary file_hashes, err = {}", part, err),
+			EDElementParseError(err, i) => write!(f, "Error interpreting EDElement from file_hashes, linecount = {}, err = {}", i, err),
+			ChecksumLengthMismatch(i) => {
+				write!(f, "File checksum at linecount = {} does not match the hash algorithm of file_hashes", i)
+			},
 			XorChecksumMismatch => write!(f, "Mismatch between xor checksum in file and generated xor checksum"),
 			FinChecksumMismatch => write!(f, "Mismatch between final checksum in file and generated final checksum"),
 			WriteBackupError(err) => write!(f, "Error writing backup, err = {}", err),
+			RestoreBackupError(err) => write!(f, "Error restoring file_hashes from a backup, err = {}", err),
+			IgnoreFilesError(err) => write!(f, "{}", err),
 		}
 	}
 }
+impl EDListOpenError {
+	/// Returns whether the error means that the contents of file_hashes are damaged,
+	/// such that a backup might be used instead.
+	pub fn is_corruption(&self) -> bool {
+		use EDListOpenError::*;
+		matches!(
+			self,
+			ChecksumsMissingError |
+				InvalidHashAlgorithm |
+				InvalidXorChecksum |
+				InvalidIgnoreFilesChecksum |
+				InvalidIgnoreFiles |
+				UndecodableXorChecksum(_) |
+				InvalidFinChecksum |
+				DecompressionError(_) |
+				BinaryFormatError(_, _) |
+				EDElementParseError(_, _) |
+				ChecksumLengthMismatch(_) |
+				XorChecksumMismatch |
+				FinChecksumMismatch
+		)
+	}
+}
 impl From<std::io::Error> for EDListOpenError {
 	fn from(err: std::io::Error) -> EDListOpenError {
 		EDListOpenError::IoError(err)
@@ -59,6 +100,11 @@ impl From<WriteBackupError> for EDListOpenError {
 		EDListOpenError::WriteBackupError(err)
 	}
 }
+impl From<IndexError> for EDListOpenError {
+	fn from(err: IndexError) -> EDListOpenError {
+		EDListOpenError::IgnoreFilesError(err)
+	}
+}
 
 #[derive(Debug)]
 pub enum UnsupportedEDListVersion {
@@ -96,11 +142,30 @@ impl std::fmt::Display for VerifyError {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		use VerifyError::*;
 		match self {
-			PathInBanlist(path) => write!(f, "\"{}\" is in the banlist.", path),
+			PathInBanlist(path) => write!(f, "\"{}\" is excluded by the banlist or an ignore file.", path),
 			EDElementError(err) => write!(f, "{}", err),
 		}
 	}
 }
+impl VerifyError {
+	/// Returns "PathInBanlist", or the kind of the EDElementError.
+	pub fn kind(&self) -> &'static str {
+		use VerifyError::*;
+		match self {
+			PathInBanlist(_) => "PathInBanlist",
+			EDElementError(err) => err.kind(),
+		}
+	}
+
+	/// Returns the path of the element that the error occurred on.
+	pub fn path(&self) -> &str {
+		use VerifyError::*;
+		match self {
+			PathInBanlist(path) => path,
+			EDElementError(err) => err.path(),
+		}
+	}
+}
 impl From<e_d_element::errors::EDElementError> for VerifyError {
 	fn from(err: e_d_element::errors::EDElementError) -> VerifyError {
 		VerifyError::EDElementError(err)
@@ -139,6 +204,11 @@ pub enum IndexError {
 	CantGetSubDirError(String, String),
 	IoError(std::io::Error),
 	OsStringConvertError(String),
+	ReadIgnoreFileError(String, std::io::Error),
+	InvalidIgnoreFile(String, OpenPathBanlistError),
+	IgnoreFilesChanged(Vec<String>),
+	AcceptedIgnoreFilesChanged(Vec<String>),
+	IgnoreFilesRejected,
 }
 impl std::error::Error for IndexError {}
 impl std::fmt::Display for IndexError {
@@ -148,6 +218,21 @@ impl std::fmt::Display for IndexError {
 			CantGetSubDirError(path, err) => write!(f, "Error getting subdirs from dir {}, error = {}", path, err),
 			IoError(err) => write!(f, "IoError during indexing, err = {}", err),
 			OsStringConvertError(path) => write!(f, "Failed to convert OsString to String in path: {}", path),
+			ReadIgnoreFileError(path, err) => write!(f, "Error reading the ignore file {}, err = {}", path, err),
+			InvalidIgnoreFile(path, err) => write!(f, "Invalid ignore file {}, err = {}", path, err),
+			IgnoreFilesChanged(paths) => write!(
+				f,
+				"The .file_hasher_ignore files have changed since file_hashes was written, the ignore files are now:\n{}\nIf the current \
+				 ignore files are correct, accept them using the acceptignorefiles operation.",
+				if paths.is_empty() { "There are no ignore files anymore.".to_string() } else { paths.join("\n") }
+			),
+			AcceptedIgnoreFilesChanged(paths) => write!(
+				f,
+				"An accepted .file_hasher_ignore file has been changed or removed since file_hashes was written, the accepted ignore \
+				 files are:\n{}\nIf the current ignore files are correct, accept them using the acceptignorefiles operation.",
+				paths.join("\n")
+			),
+			IgnoreFilesRejected => write!(f, "The changed .file_hasher_ignore files were not accepted"),
 		}
 	}
 }
@@ -161,7 +246,6 @@ impl From<std::io::Error> for IndexError {
 #[derive(Debug)]
 pub enum WriteBackupError {
 	CreateDirectoryError(String),
-	CreateFileError(String),
 	WriteEDListToFileError(WriteEDListToFileError),
 }
 impl std::error::Error for WriteBackupError {}
@@ -170,7 +254,6 @@ impl std::fmt::Display for WriteBackupError {
 		use WriteBackupError::*;
 		match self {
 			CreateDirectoryError(err) => write!(f, "Error creating hash_file_backups directory, Error = {}", err),
-			CreateFileError(err) => write!(f, "Error creating backup file, err = {}", err),
 			WriteEDListToFileError(err) => write!(f, "{}", err),
 		}
 	}
@@ -183,7 +266,6 @@ impl From<WriteEDListToFileError> for WriteBackupError {
 #[derive(Debug)]
 pub enum WriteEDListToFileError {
 	WriteError(String, String),
-	FlushError(String, String),
 }
 impl std::error::Error for WriteEDListToFileError {}
 impl std::fmt::Display for WriteEDListToFileError {
@@ -191,14 +273,13 @@ impl std::fmt::Display for WriteEDListToFileError {
 		use WriteEDListToFileError::*;
 		match self {
 			WriteError(file_name, err) => write!(f, "Error writing to the file {}. err = {}", file_name, err),
-			FlushError(file_name, err) => write!(f, "Error flushing the file {}. err = {}", file_name, err),
 		}
 	}
 }
 #[derive(Debug)]
 pub enum WriteHashFileError {
 	WriteEDListToFileError(WriteEDListToFileError),
-	ErrorCreatingFile(String),
+	ReadOnlyList,
 }
 impl std::error::Error for WriteHashFileError {}
 impl std::fmt::Display for WriteHashFileError {
@@ -206,7 +287,7 @@ impl std::fmt::Display for WriteHashFileError {
 		use WriteHashFileError::*;
 		match self {
 			WriteEDListToFileError(err) => write!(f, "{}", err),
-			ErrorCreatingFile(err) => write!(f, "Error creating file, Error = {}", err),
+			ReadOnlyList => write!(f, "The list was opened read-only, and can't be written"),
 		}
 	}
 }
@@ -217,18 +298,18 @@ impl From<WriteEDListToFileError> for WriteHashFileError {
 }
 
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum SyncFromError {
 	OpenPathBanlistError(OpenPathBanlistError),
 	EDListOpenError(EDListOpenError),
 	GetPathParentError,
 	IoError(std::io::Error),
 	InvalidUtf8Link(String),
-	ChecksumValidationError {
-		source_rel:      Checksum,
-		target_rel:      Checksum,
-		negated_rel:     Checksum,
-		new_negated_rel: Checksum,
-	},
+	HashAlgorithmMismatch(HashAlgorithm, HashAlgorithm),
+	SourceMetadataMissing,
+	TargetMetadataMissing,
+	ChecksumValidationError(Box<SyncChecksums>),
+	OutdatedSyncPlan,
 	UserAbort,
 }
 impl std::error::Error for SyncFromError {}
@@ -241,13 +322,32 @@ impl std::fmt::Display for SyncFromError {
 			GetPathParentError => write!(f, "Error getting parent of path during move or copy operation"),
 			IoError(err) => write!(f, "IOError During sync FileOperation: {}", err),
 			InvalidUtf8Link(err) => write!(f, "Invalid UTF-8 symbolic link: {}", err),
-			ChecksumValidationError { source_rel, target_rel, negated_rel, new_negated_rel } => write!(
+			HashAlgorithmMismatch(source, target) => write!(
+				f,
+				"The source list is hashed using {}, but this list is hashed using {}, lists can only be synced when they use the \
+				 same hash algorithm",
+				source, target
+			),
+			SourceMetadataMissing => write!(
+				f,
+				"Elements of the source list are missing the file sizes or the nanoseconds of the modified times, which this list \
+				 stores, so unchanged files can't be found.\nRun the migrate operation in the source folder, and the update operation \
+				 for its modified files, before syncing."
+			),
+			TargetMetadataMissing => write!(
+				f,
+				"Elements of this list are missing the file sizes or the nanoseconds of the modified times, which the source list \
+				 stores, so unchanged files can't be found.\nRun the migrate operation, and the update operation for the modified \
+				 files, before syncing."
+			),
+			ChecksumValidationError(checksums) => write!(
 				f,
 				"There was an error validating the sync operations\nPlease restore the latest EDList backup.\nDebugging \
 				 info:\nsource_relative_checksum = {}, source_relative_checksum = {}, negated_relative_checksum = {}, \
 				 new_negated_relative_checksum = {}",
-				source_rel, target_rel, negated_rel, new_negated_rel
+				checksums.source_rel, checksums.target_rel, checksums.negated_rel, checksums.new_negated_rel
 			),
+			OutdatedSyncPlan => write!(f, "The list was changed after the sync was planned, please plan the sync again"),
 			UserAbort => write!(f, "Operation aborted due to user aborting."),
 		}
 	}
@@ -267,3 +367,166 @@ impl From<std::io::Error> for SyncFromError {
 		SyncFromError::IoError(err)
 	}
 }
+
+/// SyncChecksums are the relative checksums, that didn't match when the
+/// operations of a sync were validated.
+/// They are boxed in SyncFromError, since they are much larger than the other errors.
+#[derive(Debug)]
+pub struct SyncChecksums {
+	pub source_rel:      Checksum,
+	pub target_rel:      Checksum,
+	pub negated_rel:     Checksum,
+	pub new_negated_rel: Checksum,
+}
+
+#[derive(Debug)]
+pub enum SetHashAlgorithmError {
+	ListNotEmpty(HashAlgorithm),
+}
+impl std::error::Error for SetHashAlgorithmError {}
+impl std::fmt::Display for SetHashAlgorithmError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		use SetHashAlgorithmError::*;
+		match self {
+			ListNotEmpty(hash_algorithm) => write!(
+				f,
+				"The list is hashed using {}, the hash algorithm can only be changed while the list is empty",
+				hash_algorithm
+			),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum DiffError {
+	HashAlgorithmMismatch(HashAlgorithm, HashAlgorithm),
+}
+impl std::error::Error for DiffError {}
+impl std::fmt::Display for DiffError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		use DiffError::*;
+		match self {
+			HashAlgorithmMismatch(old, new) => write!(
+				f,
+				"The old list is hashed using {}, but the new list is hashed using {}, only lists using the same hash algorithm can be \
+				 compared",
+				old, new
+			),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum PruneBackupsError {
+	ReadOnlyList,
+	ReadBackupsError(std::io::Error),
+	DeleteError(String, std::io::Error),
+}
+impl std::error::Error for PruneBackupsError {}
+impl std::fmt::Display for PruneBackupsError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		use PruneBackupsError::*;
+		match self {
+			ReadOnlyList => write!(f, "The list was opened read-only, and has no backups"),
+			ReadBackupsError(err) => write!(f, "Error reading the backups in hash_file_backups, err = {}", err),
+			DeleteError(name, err) => write!(f, "Error deleting backup {}, err = {}", name, err),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum ReadManifestError {
+	OpenError(String, std::io::Error),
+	IoError(String, std::io::Error),
+	HashAlgorithmMismatch(HashAlgorithm, HashAlgorithm),
+}
+impl std::error::Error for ReadManifestError {}
+impl std::fmt::Display for ReadManifestError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		use ReadManifestError::*;
+		match self {
+			OpenError(path, err) => write!(f, "Error opening manifest {}, err = {}", path, err),
+			IoError(path, err) => write!(f, "Error reading manifest {}, err = {}", path, err),
+			HashAlgorithmMismatch(manifest, list) => write!(
+				f,
+				"The manifest is hashed using {}, but the list is hashed using {}, only manifests using the hash algorithm of the list \
+				 can be imported",
+				manifest, list
+			),
+		}
+	}
+}
+
+/// ManifestLineError describes why a line of a manifest could not
+/// be imported or verified, the first field is the line number.
+#[derive(Debug)]
+pub enum ManifestLineError {
+	UnparsableLine(usize),
+	UnsupportedPath(usize, String),
+	ChecksumLengthMismatch(usize, String),
+	PathInBanlist(usize, String),
+	PathInList(usize, String),
+	NotAFile(usize, String),
+	EDElementError(usize, e_d_element::errors::EDElementError),
+}
+impl std::error::Error for ManifestLineError {}
+impl std::fmt::Display for ManifestLineError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		use ManifestLineError::*;
+		match self {
+			UnparsableLine(line) => write!(f, "Line {}: the line is not a checksum followed by a path", line),
+			UnsupportedPath(line, path) => write!(f, "Line {}: the path \"{}\" is not relative to the root directory", line, path),
+			ChecksumLengthMismatch(line, path) => {
+				write!(f, "Line {}: the checksum of \"{}\" does not have the length of the hash algorithm", line, path)
+			},
+			PathInBanlist(line, path) => write!(f, "Line {}: \"{}\" is excluded by the banlist or an ignore file", line, path),
+			PathInList(line, path) => write!(f, "Line {}: \"{}\" is already in the list", line, path),
+			NotAFile(line, path) => write!(f, "Line {}: \"{}\" is not a file", line, path),
+			EDElementError(line, err) => write!(f, "Line {}: {}", line, err),
+		}
+	}
+}
+impl ManifestLineError {
+	/// Returns the name of the variant, like "UnparsableLine", or the kind of the EDElementError.
+	pub fn kind(&self) -> &'static str {
+		use ManifestLineError::*;
+		match self {
+			UnparsableLine(_) => "UnparsableLine",
+			UnsupportedPath(_, _) => "UnsupportedPath",
+			ChecksumLengthMismatch(_, _) => "ChecksumLengthMismatch",
+			PathInBanlist(_, _) => "PathInBanlist",
+			PathInList(_, _) => "PathInList",
+			NotAFile(_, _) => "NotAFile",
+			EDElementError(_, err) => err.kind(),
+		}
+	}
+
+	/// Returns the path of the line that the error occurred on,
+	/// or None if the line could not be parsed.
+	pub fn path(&self) -> Option<&str> {
+		use ManifestLineError::*;
+		match self {
+			UnparsableLine(_) => None,
+			UnsupportedPath(_, path) |
+			ChecksumLengthMismatch(_, path) |
+			PathInBanlist(_, path) |
+			PathInList(_, path) |
+			NotAFile(_, path) => Some(path),
+			EDElementError(_, err) => Some(err.path()),
+		}
+	}
+
+	/// Returns the number of the line that the error occurred on.
+	pub fn line(&self) -> usize {
+		use ManifestLineError::*;
+		match self {
+			UnparsableLine(line) |
+			UnsupportedPath(line, _) |
+			ChecksumLengthMismatch(line, _) |
+			PathInBanlist(line, _) |
+			PathInList(line, _) |
+			NotAFile(line, _) |
+			EDElementError(line, _) => *line,
+		}
+	}
+}
@@ -0,0 +1,213 @@
+/*
+	This file is part of file_hasher.
+
+	file_hasher is free software: you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation, either version 3 of the License, or
+	(at your option) any later version.
+
+	file_hasher is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License
+	along with file_hasher.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{fs, io::ErrorKind};
+
+use blake2::{
+	digest::{Update, VariableOutput},
+	Blake2bVar,
+};
+
+use super::errors::IndexError;
+use crate::{
+	path_banlist::PathBanlist,
+	shared::{self, constants::HASH_OUTPUT_LENGTH, Checksum},
+};
+
+/// The name of the files, that exclude paths within the directory they are stored in.
+pub(super) const IGNORE_FILE_NAME: &str = ".file_hasher_ignore";
+
+/// IgnoreFile is a .file_hasher_ignore file, found while indexing.
+///
+/// It uses the syntax of the banlist, without a checksum line,
+/// but its paths are relative to the directory it is stored in,
+/// such that "./build/" in "./project/.file_hasher_ignore"
+/// excludes "./project/build/".
+#[derive(Debug, Clone)]
+pub(super) struct IgnoreFile {
+	path:      String,
+	directory: String,
+	contents:  Vec<u8>,
+	banlist:   PathBanlist,
+}
+impl IgnoreFile {
+	/// Reads the ignore file of the directory, if the directory has one.
+	/// The path of the directory must end with a forward slash.
+	pub(super) fn read(directory: &str) -> Result<Option<IgnoreFile>, IndexError> {
+		let path = format!("{}{}", directory, IGNORE_FILE_NAME);
+		let contents = match fs::read(&path) {
+			Ok(contents) => contents,
+			Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+			Err(err) => return Err(IndexError::ReadIgnoreFileError(path, err)),
+		};
+		let banlist = PathBanlist::from_ignore_file(&String::from_utf8_lossy(&contents))
+			.map_err(|err| IndexError::InvalidIgnoreFile(path.clone(), err))?;
+		Ok(Some(IgnoreFile { path, directory: directory.to_string(), contents, banlist }))
+	}
+
+	pub(super) fn get_path(&self) -> &str {
+		&self.path
+	}
+
+	/// Returns the path relative to the directory of the ignore file, starting with "./",
+	/// or None if the path isn't within the directory.
+	fn relative_path(&self, path: &str) -> Option<String> {
+		path.strip_prefix(&self.directory)
+			.filter(|relative_path| !relative_path.is_empty())
+			.map(|relative_path| format!("./{}", relative_path))
+	}
+}
+
+/// Used to test whether a path is excluded by the banlist, or by the ignore files.
+/// Paths of directories must end with a forward slash.
+///
+/// The banlist and the ignore files, whose directories contain the path, are scopes that are
+/// evaluated from the outermost to the innermost, and like the lines of a banlist, the last scope
+/// with a rule that matches the path decides. So a negation line in a nested ignore file includes
+/// a path, that the banlist or the ignore file of a parent directory excludes.
+pub(super) fn is_excluded(banlist: &PathBanlist, ignore_files: &[IgnoreFile], path: &str) -> bool {
+	innermost_decision(ignore_files, path, |ignore_file, relative_path| ignore_file.banlist.decision(relative_path))
+		.or_else(|| banlist.decision(path))
+		.unwrap_or(false)
+}
+
+/// Used to test whether a directory, and everything within it, is excluded,
+/// see PathBanlist::is_directory_in_banlist.
+///
+/// Ignore files within the directory are not considered, since they
+/// aren't read when the directory is skipped during indexing.
+pub(super) fn is_directory_excluded(banlist: &PathBanlist, ignore_files: &[IgnoreFile], directory: &str) -> bool {
+	is_excluded(banlist, ignore_files, directory) &&
+		!banlist.may_unban_within(directory) &&
+		!ignore_files.iter().any(|ignore_file| {
+			ignore_file
+				.relative_path(directory)
+				.is_some_and(|relative_path| ignore_file.banlist.may_unban_within(&relative_path))
+		})
+}
+
+/// Returns the rule that excludes the path, see is_excluded, or None if the path isn't excluded.
+/// The rules of ignore files are prefixed by the path of the ignore file, see rules.
+pub(super) fn excluding_rule(banlist: &PathBanlist, ignore_files: &[IgnoreFile], path: &str) -> Option<String> {
+	let (rule, is_excluded) = innermost_decision(ignore_files, path, |ignore_file, relative_path| {
+		let (rule, is_excluded) = ignore_file.banlist.deciding_rule(relative_path)?;
+		Some((rule_of_ignore_file(ignore_file, rule), is_excluded))
+	})
+	.or_else(|| banlist.deciding_rule(path).map(|(rule, is_excluded)| (rule.to_string(), is_excluded)))?;
+	is_excluded.then_some(rule)
+}
+
+/// Returns the rules of the banlist, followed by the rules of the ignore files,
+/// which are prefixed by the path of their ignore file.
+pub(super) fn rules(banlist: &PathBanlist, ignore_files: &[IgnoreFile]) -> Vec<String> {
+	let ignore_file_rules = ignore_files
+		.iter()
+		.flat_map(|ignore_file| ignore_file.banlist.get_rules().map(|rule| rule_of_ignore_file(ignore_file, rule)));
+	banlist.get_rules().map(String::from).chain(ignore_file_rules).collect()
+}
+
+fn rule_of_ignore_file(ignore_file: &IgnoreFile, rule: &str) -> String {
+	format!("{}: {}", ignore_file.path, rule)
+}
+
+/// Returns the decision of the innermost ignore file, that contains the path,
+/// and has a decision about it, or None if there is no such ignore file.
+fn innermost_decision<T>(ignore_files: &[IgnoreFile], path: &str, decision: impl Fn(&IgnoreFile, &str) -> Option<T>) -> Option<T> {
+	ignore_files
+		.iter()
+		.filter_map(|ignore_file| Some((ignore_file.directory.len(), decision(ignore_file, &ignore_file.relative_path(path)?)?)))
+		.max_by_key(|(directory_length, _)| *directory_length)
+		.map(|(_, decision)| decision)
+}
+
+/// AcceptedIgnoreFiles is the paths and the combined checksum of the ignore files,
+/// that were accepted for a list, they are stored in its file_hashes file.
+///
+/// The paths are sorted, so that only the accepted ignore files have to be read,
+/// when the list is opened, new ignore files are found when the directory is indexed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct AcceptedIgnoreFiles {
+	paths:    Vec<String>,
+	checksum: Checksum,
+}
+impl AcceptedIgnoreFiles {
+	pub(super) fn new(mut paths: Vec<String>, checksum: Checksum) -> AcceptedIgnoreFiles {
+		paths.sort();
+		AcceptedIgnoreFiles { paths, checksum }
+	}
+
+	/// Returns the accepted ignore files of a list without any ignore files.
+	pub(super) fn none() -> AcceptedIgnoreFiles {
+		AcceptedIgnoreFiles::from_ignore_files(&[])
+	}
+
+	pub(super) fn from_ignore_files(ignore_files: &[IgnoreFile]) -> AcceptedIgnoreFiles {
+		let paths = ignore_files.iter().map(|ignore_file| ignore_file.path.clone()).collect();
+		AcceptedIgnoreFiles::new(paths, combined_checksum(ignore_files))
+	}
+
+	pub(super) fn get_paths(&self) -> &[String] {
+		&self.paths
+	}
+
+	pub(super) fn get_checksum(&self) -> &Checksum {
+		&self.checksum
+	}
+
+	/// Adds the accepted ignore files to the final checksum of a list.
+	pub(super) fn update_hasher(&self, hasher: &mut Blake2bVar) {
+		hasher.update(self.checksum.as_ref());
+		for path in &self.paths {
+			hasher.update(&(path.len() as u64).to_le_bytes());
+			hasher.update(path.as_bytes());
+		}
+	}
+
+	/// Reads the accepted ignore files, without searching for new ones.
+	///
+	/// Returns an AcceptedIgnoreFilesChanged error,
+	/// if any of them has been changed or removed since they were accepted.
+	pub(super) fn read(&self) -> Result<Vec<IgnoreFile>, IndexError> {
+		let changed_error = || IndexError::AcceptedIgnoreFilesChanged(self.paths.clone());
+		let mut ignore_files = Vec::new();
+		for path in &self.paths {
+			let directory = path.strip_suffix(IGNORE_FILE_NAME).ok_or_else(changed_error)?;
+			ignore_files.push(IgnoreFile::read(directory)?.ok_or_else(changed_error)?);
+		}
+		if combined_checksum(&ignore_files) != self.checksum {
+			return Err(changed_error());
+		}
+		Ok(ignore_files)
+	}
+}
+
+/// Generates the checksum of a set of ignore files, from their paths and contents,
+/// which doesn't depend on the order the ignore files were found in.
+fn combined_checksum(ignore_files: &[IgnoreFile]) -> Checksum {
+	let mut sorted_ignore_files: Vec<_> = ignore_files.iter().collect();
+	sorted_ignore_files.sort_by(|a, b| a.path.cmp(&b.path));
+
+	let mut hasher = Blake2bVar::new(HASH_OUTPUT_LENGTH).unwrap();
+	for ignore_file in sorted_ignore_files {
+		// The lengths are included, so that the boundaries between the files can't be moved.
+		hasher.update(&(ignore_file.path.len() as u64).to_le_bytes());
+		hasher.update(ignore_file.path.as_bytes());
+		hasher.update(&(ignore_file.contents.len() as u64).to_le_bytes());
+		hasher.update(&ignore_file.contents);
+	}
+	shared::blake2_to_checksum(hasher)
+}
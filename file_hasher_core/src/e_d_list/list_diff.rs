@@ -0,0 +1,150 @@
+/*
+	This file is part of file_hasher.
+
+	file_hasher is free software: you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation, either version 3 of the License, or
+	(at your option) any later version.
+
+	file_hasher is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License
+	along with file_hasher.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::{HashMap, HashSet};
+
+use super::e_d_element::{EDElement, EDVariantFields};
+use crate::shared::FileChecksum;
+
+/// ListDifference describes how an element differs between
+/// an old and a new version of a list.
+///
+/// A file is Moved, when a file that only exists in the old list
+/// has the same checksum as a file that only exists in the new list.
+/// Elements whose content is unchanged, but whose modified time
+/// has changed, are ModifiedTimeChanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListDifference {
+	Added(String),
+	Removed(String),
+	ContentChanged(String),
+	ModifiedTimeChanged(String),
+	LinkRetargeted { path: String, old_target: String, new_target: String },
+	Moved { from: String, to: String },
+}
+impl ListDifference {
+	/// Returns the name of the variant, like "Added" or "Moved".
+	pub fn kind(&self) -> &'static str {
+		use ListDifference::*;
+		match self {
+			Added(_) => "Added",
+			Removed(_) => "Removed",
+			ContentChanged(_) => "ContentChanged",
+			ModifiedTimeChanged(_) => "ModifiedTimeChanged",
+			LinkRetargeted { .. } => "LinkRetargeted",
+			Moved { .. } => "Moved",
+		}
+	}
+
+	/// Returns the path of the element in the new list,
+	/// or in the old list for removed elements.
+	pub fn path(&self) -> &str {
+		use ListDifference::*;
+		match self {
+			Added(path) | Removed(path) | ContentChanged(path) | ModifiedTimeChanged(path) => path,
+			LinkRetargeted { path, .. } => path,
+			Moved { to, .. } => to,
+		}
+	}
+
+	/// Returns the path of a moved file in the old list.
+	pub fn old_path(&self) -> Option<&str> {
+		match self {
+			ListDifference::Moved { from, .. } => Some(from),
+			_ => None,
+		}
+	}
+}
+impl std::fmt::Display for ListDifference {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		use ListDifference::*;
+		match self {
+			Added(path) => write!(f, "Added {}", path),
+			Removed(path) => write!(f, "Removed {}", path),
+			ContentChanged(path) => write!(f, "Content changed {}", path),
+			ModifiedTimeChanged(path) => write!(f, "Modified time changed {}", path),
+			LinkRetargeted { path, old_target, new_target } => write!(f, "Link retargeted {} from {} to {}", path, old_target, new_target),
+			Moved { from, to } => write!(f, "Moved {} to {}", from, to),
+		}
+	}
+}
+
+/// Finds the differences between the elements of an old and a new list,
+/// whose files must be hashed using the same hash algorithm.
+///
+/// The differences are sorted by their paths.
+pub(super) fn diff(old_list: &[EDElement], new_list: &[EDElement]) -> Vec<ListDifference> {
+	let old_elements = old_list.iter().map(|element| (element.get_path(), element)).collect::<HashMap<_, _>>();
+	let new_elements = new_list.iter().map(|element| (element.get_path(), element)).collect::<HashMap<_, _>>();
+	let mut differences = Vec::new();
+
+	for new_element in new_list {
+		if let Some(old_element) = old_elements.get(new_element.get_path()) {
+			if let Some(difference) = compare(old_element, new_element) {
+				differences.push(difference);
+			}
+		}
+	}
+
+	// Files that only exist in one of the lists are moved, if their checksums match.
+	let mut removed = old_list.iter().filter(|element| !new_elements.contains_key(element.get_path())).collect::<Vec<_>>();
+	let mut added = new_list.iter().filter(|element| !old_elements.contains_key(element.get_path())).collect::<Vec<_>>();
+	removed.sort_by(|a, b| a.get_path().cmp(b.get_path()));
+	added.sort_by(|a, b| a.get_path().cmp(b.get_path()));
+	let mut removed_files: HashMap<&FileChecksum, Vec<&str>> = HashMap::new();
+	for element in removed.iter().rev() {
+		if let EDVariantFields::File { checksum, size: _ } = element.get_variant() {
+			removed_files.entry(checksum).or_default().push(element.get_path());
+		}
+	}
+	let mut moved_from = HashSet::new();
+	for element in added {
+		let from = match element.get_variant() {
+			EDVariantFields::File { checksum, size: _ } => removed_files.get_mut(checksum).and_then(|paths| paths.pop()),
+			EDVariantFields::Link { target: _ } => None,
+		};
+		match from {
+			Some(from) => {
+				moved_from.insert(from);
+				differences.push(ListDifference::Moved { from: from.to_string(), to: element.get_path().to_string() });
+			},
+			None => differences.push(ListDifference::Added(element.get_path().to_string())),
+		}
+	}
+	differences.extend(
+		removed
+			.into_iter()
+			.filter(|element| !moved_from.contains(element.get_path()))
+			.map(|element| ListDifference::Removed(element.get_path().to_string())),
+	);
+
+	differences.sort_by(|a, b| a.path().cmp(b.path()));
+	differences
+}
+
+/// Compares two elements with the same path.
+fn compare(old_element: &EDElement, new_element: &EDElement) -> Option<ListDifference> {
+	let path = new_element.get_path().to_string();
+	match (old_element.get_variant(), new_element.get_variant()) {
+		(EDVariantFields::Link { target: old_target }, EDVariantFields::Link { target: new_target }) if old_target != new_target => {
+			Some(ListDifference::LinkRetargeted { path, old_target: old_target.clone(), new_target: new_target.clone() })
+		},
+		(old_variant, new_variant) if !old_variant.has_same_content(new_variant) => Some(ListDifference::ContentChanged(path)),
+		_ if !old_element.get_modified_time().matches(&new_element.get_modified_time()) => Some(ListDifference::ModifiedTimeChanged(path)),
+		_ => None,
+	}
+}
@@ -0,0 +1,523 @@
+/*
+	This file is part of file_hasher.
+
+	file_hasher is free software: you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation, either version 3 of the License, or
+	(at your option) any later version.
+
+	file_hasher is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License
+	along with file_hasher.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{
+	borrow::Cow,
+	io::{self, BufRead, BufReader, Read, Write},
+};
+
+use blake2::{
+	digest::{Update, VariableOutput},
+	Blake2bVar,
+};
+use flate2::{bufread::GzDecoder, write::GzEncoder, Compression};
+use join::try_join;
+use rayon::prelude::*;
+
+use super::{
+	binary_format::{self, BINARY_MAGIC},
+	e_d_element::{EDElement, EDVariantFields},
+	errors::*,
+	ignore_files::AcceptedIgnoreFiles,
+};
+use crate::shared::{self, constants::*, Checksum, HashAlgorithm};
+
+/// ListVersion identifies the versions of the file_hashes format,
+/// that can be read by this version of file_hasher.
+///
+/// * 1.1 stores modified times in whole seconds, and no file sizes.
+/// * 1.2 adds the size of files.
+/// * 1.3 adds nanoseconds to the modified times.
+/// * 1.4 adds a header line with the hash algorithm of the file checksums,
+///   which is also included in the final checksum.
+/// * 1.5 adds a header line with the combined checksum of the accepted .file_hasher_ignore files,
+///   and a header line with their paths, which are also included in the final checksum.
+///
+/// Every later version is a superset of the earlier versions,
+/// elements from earlier versions are read with their missing
+/// fields set to None, which keeps their element hashes unchanged.
+/// Lists from before 1.4 are read as using BLAKE2b-256,
+/// and lists from before 1.5 are read as having no ignore files.
+/// Lists are always written in the version CURRENT_LIST_VERSION.
+///
+/// Lists of version 1.0 use other element and checksum rules, which this version
+/// of file_hasher doesn't implement, so they are rejected, see UnsupportedEDListVersion::V1_0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ListVersion {
+	V1_1,
+	V1_2,
+	V1_3,
+	V1_4,
+	V1_5,
+}
+impl ListVersion {
+	/// Returns the version that lists are written in.
+	pub fn current() -> ListVersion {
+		ListVersion::from_identifier(CURRENT_LIST_VERSION).expect("CURRENT_LIST_VERSION must be a readable version")
+	}
+
+	fn from_identifier(identifier: &str) -> Option<ListVersion> {
+		match identifier {
+			"1.1" => Some(ListVersion::V1_1),
+			"1.2" => Some(ListVersion::V1_2),
+			"1.3" => Some(ListVersion::V1_3),
+			"1.4" => Some(ListVersion::V1_4),
+			"1.5" => Some(ListVersion::V1_5),
+			_ => None,
+		}
+	}
+
+	/// Interprets the first line of a file_hashes file.
+	fn from_line(line: &str) -> Result<ListVersion, UnsupportedEDListVersion> {
+		match line.strip_prefix(LIST_VERSION_PREFIX) {
+			Some("1.0") => Err(UnsupportedEDListVersion::V1_0),
+			Some(identifier) => {
+				ListVersion::from_identifier(identifier).ok_or_else(|| UnsupportedEDListVersion::Invalid(identifier.to_owned()))
+			},
+			None => Err(UnsupportedEDListVersion::MissingIdentifier),
+		}
+	}
+
+	pub fn identifier(&self) -> &'static str {
+		match self {
+			ListVersion::V1_1 => "1.1",
+			ListVersion::V1_2 => "1.2",
+			ListVersion::V1_3 => "1.3",
+			ListVersion::V1_4 => "1.4",
+			ListVersion::V1_5 => "1.5",
+		}
+	}
+}
+impl std::fmt::Display for ListVersion {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "{}", self.identifier())
+	}
+}
+
+/// The first bytes of every gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// ListFormat is the format that the elements of a file_hashes file are stored in.
+///
+/// Text lists are written in the version CURRENT_LIST_VERSION.
+/// Binary lists contain the same information in a compact form,
+/// that is read and written one element at a time, see binary_format.
+/// The format is detected from the first bytes of the list when it is read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListFormat {
+	#[default]
+	Text,
+	Binary,
+}
+impl ListFormat {
+	pub fn identifier(&self) -> &'static str {
+		match self {
+			ListFormat::Text => "text",
+			ListFormat::Binary => "binary",
+		}
+	}
+}
+impl std::str::FromStr for ListFormat {
+	type Err = &'static str;
+
+	fn from_str(identifier: &str) -> Result<ListFormat, Self::Err> {
+		match identifier.to_lowercase().as_str() {
+			"text" => Ok(ListFormat::Text),
+			"binary" => Ok(ListFormat::Binary),
+			_ => Err("Valid list formats are text/binary"),
+		}
+	}
+}
+impl std::fmt::Display for ListFormat {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "{}", self.identifier())
+	}
+}
+
+/// ListCompression is the encoding that a file_hashes file is stored in.
+///
+/// A compressed list contains an uncompressed list of either format, the
+/// compression is detected from the first bytes of the file when it is read,
+/// so every version of the list format can be stored compressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListCompression {
+	#[default]
+	None,
+	Gzip,
+}
+impl ListCompression {
+	pub fn identifier(&self) -> &'static str {
+		match self {
+			ListCompression::None => "none",
+			ListCompression::Gzip => "gzip",
+		}
+	}
+
+	/// Detects the compression of a list from its first bytes.
+	fn detect(start: &[u8]) -> ListCompression {
+		if start.starts_with(&GZIP_MAGIC) { ListCompression::Gzip } else { ListCompression::None }
+	}
+}
+impl std::str::FromStr for ListCompression {
+	type Err = &'static str;
+
+	fn from_str(identifier: &str) -> Result<ListCompression, Self::Err> {
+		match identifier.to_lowercase().as_str() {
+			"none" => Ok(ListCompression::None),
+			"gzip" => Ok(ListCompression::Gzip),
+			_ => Err("Valid compressions are none/gzip"),
+		}
+	}
+}
+impl std::fmt::Display for ListCompression {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "{}", self.identifier())
+	}
+}
+
+/// The contents of a file_hashes file, whose checksums have been validated.
+pub(super) struct ReadList {
+	pub version:               ListVersion,
+	pub format:                ListFormat,
+	pub hash_algorithm:        HashAlgorithm,
+	pub compression:           ListCompression,
+	pub element_list:          Vec<EDElement>,
+	pub xor_checksum:          Checksum,
+	pub accepted_ignore_files: AcceptedIgnoreFiles,
+}
+impl ReadList {
+	/// Returns an empty list of the current version,
+	/// using the default hash algorithm, format and compression,
+	/// and without any ignore files.
+	pub fn empty() -> ReadList {
+		ReadList {
+			version:               ListVersion::current(),
+			format:                ListFormat::default(),
+			hash_algorithm:        HashAlgorithm::default(),
+			compression:           ListCompression::default(),
+			element_list:          Vec::new(),
+			xor_checksum:          Checksum::default(),
+			accepted_ignore_files: AcceptedIgnoreFiles::none(),
+		}
+	}
+}
+
+/// Reads a list of any readable version, format and compression,
+/// and validates its xor_checksum and final checksum, before returning it.
+pub(super) fn read_list(mut reader: impl BufRead) -> Result<ReadList, EDListOpenError> {
+	let compression = ListCompression::detect(reader.fill_buf()?);
+	match compression {
+		ListCompression::None => read_uncompressed_list(reader, compression),
+		ListCompression::Gzip => {
+			read_uncompressed_list(BufReader::new(GzDecoder::new(reader)), compression).map_err(|err| match err {
+				// Errors reading a compressed list come from decompressing it.
+				EDListOpenError::IoError(err) => EDListOpenError::DecompressionError(err),
+				err => err,
+			})
+		},
+	}
+}
+
+fn read_uncompressed_list(mut reader: impl BufRead, compression: ListCompression) -> Result<ReadList, EDListOpenError> {
+	if reader.fill_buf()?.starts_with(BINARY_MAGIC) {
+		binary_format::read_list(reader, compression)
+	}
+	else {
+		read_text_list(reader, compression)
+	}
+}
+
+fn read_text_list(reader: impl BufRead, compression: ListCompression) -> Result<ReadList, EDListOpenError> {
+	let mut lines = reader.lines().collect::<Result<Vec<_>, _>>()?.into_iter();
+
+	// Handling list version.
+	let version = ListVersion::from_line(lines.next().ok_or(EDListOpenError::ChecksumsMissingError)?.as_ref())?;
+
+	// Lists from before version 1.4 don't store the hash algorithm.
+	let hash_algorithm = if version >= ListVersion::V1_4 {
+		let hash_algorithm_line = lines.next().ok_or(EDListOpenError::ChecksumsMissingError)?;
+		let identifier = hash_algorithm_line
+			.strip_prefix(HASH_ALGORITHM_PREFIX)
+			.ok_or(EDListOpenError::InvalidHashAlgorithm)?;
+		identifier.parse().map_err(|_| EDListOpenError::InvalidHashAlgorithm)?
+	}
+	else {
+		HashAlgorithm::Blake2b256
+	};
+
+	// Lists from before version 1.5 don't store the accepted ignore files.
+	let accepted_ignore_files = if version >= ListVersion::V1_5 {
+		let (ignore_files_checksum_line, ignore_files_line) =
+			try_join!(lines.next(), lines.next()).ok_or(EDListOpenError::ChecksumsMissingError)?;
+		let ignore_files_checksum = ignore_files_checksum_line
+			.strip_prefix(IGNORE_FILES_CHECKSUM_PREFIX)
+			.and_then(|checksum_string| Checksum::from_hex(checksum_string).ok())
+			.ok_or(EDListOpenError::InvalidIgnoreFilesChecksum)?;
+		let ignore_file_paths = ignore_files_line
+			.strip_prefix(IGNORE_FILES_PREFIX)
+			.map(parse_ignore_file_paths)
+			.ok_or(EDListOpenError::InvalidIgnoreFiles)?;
+		AcceptedIgnoreFiles::new(ignore_file_paths, ignore_files_checksum)
+	}
+	else {
+		AcceptedIgnoreFiles::none()
+	};
+	let header_length = match version {
+		ListVersion::V1_1 | ListVersion::V1_2 | ListVersion::V1_3 => 3,
+		ListVersion::V1_4 => 4,
+		ListVersion::V1_5 => 6,
+	};
+
+	let (xor_checksum_line, fin_checksum_line) = try_join!(lines.next(), lines.next()).ok_or(EDListOpenError::ChecksumsMissingError)?;
+
+	// Parsing file_xor_checksum
+	let file_xor_checksum = if let Some(xor_checksum_string) = xor_checksum_line.strip_prefix(XOR_CHECKSUM_PREFIX) {
+		Checksum::from_hex(xor_checksum_string)?
+	}
+	else {
+		Err(EDListOpenError::InvalidXorChecksum)?
+	};
+
+	// Parsing file_final_checksum
+	let file_final_checksum = fin_checksum_line.strip_prefix(FIN_CHECKSUM_PREFIX).ok_or(EDListOpenError::InvalidFinChecksum)?;
+	let mut xor_checksum = Checksum::default();
+	let mut hasher = Blake2bVar::new(HASH_OUTPUT_LENGTH).unwrap();
+
+	// Parsing all EDElements.
+	let e_d_elements = lines
+		.collect::<Vec<_>>()
+		.into_par_iter()
+		.enumerate()
+		.map(|(i, line)| EDElement::try_from(line.as_ref()).map_err(|err| (err, i + header_length + 1)))
+		.collect::<Result<Vec<_>, _>>()?;
+
+	// Every file checksum must have been made by the hash algorithm of the list.
+	for (i, element) in e_d_elements.iter().enumerate() {
+		if let EDVariantFields::File { checksum, size: _ } = element.get_variant() {
+			if checksum.len() != hash_algorithm.output_length() {
+				Err(EDListOpenError::ChecksumLengthMismatch(i + header_length + 1))?
+			}
+		}
+	}
+
+	// Processing the checksums, so that we can verify the integrity
+	// of the file before returning.
+	e_d_elements.iter().for_each(|element| {
+		hasher.update(element.get_hash().as_ref());
+		xor_checksum ^= element.get_hash();
+	});
+	hasher.update(file_xor_checksum.as_ref());
+	if version >= ListVersion::V1_4 {
+		hasher.update(hash_algorithm.identifier().as_bytes());
+	}
+	if version >= ListVersion::V1_5 {
+		accepted_ignore_files.update_hasher(&mut hasher);
+	}
+	let final_checksum = shared::blake2_to_checksum(hasher);
+
+	// By creating the ReadList object before comparing xor_checksum with
+	// the one saved in the file_hashes file, we hopefully avoid any optimizations
+	// that would prevent the edlist from using the generated xorchecksum, after comparison.
+	let read_list = ReadList {
+		version,
+		format: ListFormat::Text,
+		hash_algorithm,
+		compression,
+		element_list: e_d_elements,
+		xor_checksum: file_xor_checksum,
+		accepted_ignore_files,
+	};
+
+	// Verifying xor_checksum
+	if read_list.xor_checksum != xor_checksum {
+		Err(EDListOpenError::XorChecksumMismatch)?
+	}
+
+	// Verifying final_checksum.
+	if file_final_checksum != final_checksum.to_string() {
+		Err(EDListOpenError::FinChecksumMismatch)?
+	}
+
+	Ok(read_list)
+}
+
+/// Writes the elements, the xor_checksum, the hash_algorithm and the accepted_ignore_files
+/// to writer, as a list in the given format and compression.
+pub(super) fn write_list(
+	writer: &mut dyn Write, element_list: &[EDElement], xor_checksum: &Checksum, hash_algorithm: HashAlgorithm,
+	accepted_ignore_files: &AcceptedIgnoreFiles, format: ListFormat, compression: ListCompression,
+) -> io::Result<()> {
+	match compression {
+		ListCompression::None => write_uncompressed_list(writer, element_list, xor_checksum, hash_algorithm, accepted_ignore_files, format),
+		ListCompression::Gzip => {
+			let mut encoder = GzEncoder::new(writer, Compression::default());
+			write_uncompressed_list(&mut encoder, element_list, xor_checksum, hash_algorithm, accepted_ignore_files, format)?;
+			encoder.finish().map(|_| ())
+		},
+	}
+}
+
+fn write_uncompressed_list(
+	writer: &mut dyn Write, element_list: &[EDElement], xor_checksum: &Checksum, hash_algorithm: HashAlgorithm,
+	accepted_ignore_files: &AcceptedIgnoreFiles, format: ListFormat,
+) -> io::Result<()> {
+	match format {
+		ListFormat::Text => writer.write_all(format_list(element_list, xor_checksum, hash_algorithm, accepted_ignore_files).as_bytes()),
+		ListFormat::Binary => binary_format::write_list(writer, element_list, xor_checksum, hash_algorithm, accepted_ignore_files),
+	}
+}
+
+/// Formats the elements, the xor_checksum, the hash_algorithm and the
+/// accepted_ignore_files as a text list of the current version.
+fn format_list(
+	element_list: &[EDElement], xor_checksum: &Checksum, hash_algorithm: HashAlgorithm, accepted_ignore_files: &AcceptedIgnoreFiles,
+) -> String {
+	let mut hasher = Blake2bVar::new(HASH_OUTPUT_LENGTH).unwrap();
+	let mut element_string = String::new();
+
+	for element in element_list {
+		element_string.push_str(format!("{}\n", element).as_ref());
+		hasher.update(element.get_hash().as_ref());
+	}
+	hasher.update(xor_checksum.as_ref());
+	hasher.update(hash_algorithm.identifier().as_bytes());
+	accepted_ignore_files.update_hasher(&mut hasher);
+
+	let list_version_string = format!("{}{}\n", LIST_VERSION_PREFIX, CURRENT_LIST_VERSION);
+	let hash_algorithm_string = format!("{}{}\n", HASH_ALGORITHM_PREFIX, hash_algorithm);
+	let ignore_files_checksum_string = format!("{}{}\n", IGNORE_FILES_CHECKSUM_PREFIX, accepted_ignore_files.get_checksum());
+	let ignore_files_string = format!("{}{}\n", IGNORE_FILES_PREFIX, format_ignore_file_paths(accepted_ignore_files.get_paths()));
+	let xor_checksum_string = format!("{}{}\n", XOR_CHECKSUM_PREFIX, hex::encode_upper(xor_checksum.as_ref()));
+	let fin_checksum_string = format!("{}{}\n", FIN_CHECKSUM_PREFIX, shared::blake2_to_checksum(hasher));
+
+	format!(
+		"{}{}{}{}{}{}{}",
+		list_version_string,
+		hash_algorithm_string,
+		ignore_files_checksum_string,
+		ignore_files_string,
+		xor_checksum_string,
+		fin_checksum_string,
+		element_string
+	)
+}
+
+/// Formats the paths of the accepted ignore files as the value of the IGNOREFILES line,
+/// the paths are separated by commas, which are escaped in the paths like in the paths of elements.
+pub(super) fn format_ignore_file_paths(paths: &[String]) -> String {
+	paths.iter().map(|path| path.replace(r"\", r"\\").replace(',', r"\,")).collect::<Vec<_>>().join(",")
+}
+
+/// Interprets the value of the IGNOREFILES line, see format_ignore_file_paths.
+fn parse_ignore_file_paths(value: &str) -> Vec<String> {
+	let mut paths = Vec::new();
+	if value.is_empty() {
+		return paths;
+	}
+	let mut path = String::new();
+	let mut char_iterator = value.chars();
+	while let Some(character) = char_iterator.next() {
+		match character {
+			'\\' => path.extend(char_iterator.next()),
+			',' => paths.push(std::mem::take(&mut path)),
+			character => path.push(character),
+		}
+	}
+	paths.push(path);
+	paths
+}
+
+/// Decodes the contents of a list file, which may be compressed, binary or damaged,
+/// into text that can be shown to the user.
+/// As much as possible of a damaged list is decoded.
+pub(super) fn decode_list_lossy(contents: &[u8]) -> String {
+	let decoded = match ListCompression::detect(contents) {
+		ListCompression::None => Cow::Borrowed(contents),
+		ListCompression::Gzip => {
+			// read_to_end keeps the bytes that were decoded before an error.
+			let mut decoded = Vec::new();
+			let _ = GzDecoder::new(contents).read_to_end(&mut decoded);
+			Cow::Owned(decoded)
+		},
+	};
+	if decoded.starts_with(BINARY_MAGIC) {
+		binary_format::format_lossy(decoded.as_ref())
+	}
+	else {
+		String::from_utf8_lossy(&decoded).into_owned()
+	}
+}
+
+/// MigrationReport describes what changes when a list,
+/// that was read from an earlier version, is converted
+/// and written in the current version, see EDList::migrate.
+///
+/// sizes_stored and nanos_stored are the amounts of elements that were converted,
+/// files_without_size and elements_without_nanos are the amounts that couldn't be.
+/// If dry_run is true, nothing was converted or written.
+#[derive(Debug)]
+pub struct MigrationReport {
+	pub from_version:           ListVersion,
+	pub to_version:             ListVersion,
+	pub hash_algorithm:         HashAlgorithm,
+	pub element_count:          usize,
+	pub sizes_stored:           usize,
+	pub nanos_stored:           usize,
+	pub files_without_size:     usize,
+	pub elements_without_nanos: usize,
+	pub dry_run:                bool,
+}
+impl std::fmt::Display for MigrationReport {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		let (is, are) = if self.dry_run { ("would be", "would be") } else { ("is", "are") };
+		if self.from_version == self.to_version {
+			writeln!(f, "file_hashes is already version {}.", self.to_version)?;
+		}
+		else {
+			writeln!(f, "file_hashes {} converted from version {} to version {}.", is, self.from_version, self.to_version)?;
+		}
+		writeln!(f, "The checksums of all {} elements have been validated.", self.element_count)?;
+		writeln!(f, "The files in the list are hashed using {}.", self.hash_algorithm)?;
+		writeln!(
+			f,
+			"The sizes of {} files, and the modified times in nanoseconds of {} elements {} stored, since their modified times are \
+			 unchanged.",
+			self.sizes_stored, self.nanos_stored, are
+		)?;
+		if self.files_without_size != 0 || self.elements_without_nanos != 0 {
+			writeln!(
+				f,
+				"{} files have no stored size, and {} elements have modified times in whole seconds, since their modified times have \
+				 changed.\nThey will be converted when they are updated.",
+				self.files_without_size, self.elements_without_nanos
+			)?;
+		}
+		if self.from_version < ListVersion::V1_5 {
+			writeln!(
+				f,
+				"No .file_hasher_ignore files are stored, so any ignore files must be accepted using the acceptignorefiles operation, \
+				 before the files can be indexed."
+			)?;
+		}
+		if self.dry_run {
+			write!(f, "This was a dry run, file_hashes has not been changed.")
+		}
+		else {
+			write!(f, "file_hashes has been converted.")
+		}
+	}
+}
@@ -0,0 +1,170 @@
+/*
+	This file is part of file_hasher.
+
+	file_hasher is free software: you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation, either version 3 of the License, or
+	(at your option) any later version.
+
+	file_hasher is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License
+	along with file_hasher.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{
+	fs::File,
+	io::{BufRead, BufReader},
+};
+
+use super::errors::{ManifestLineError, ReadManifestError};
+use crate::shared::FileChecksum;
+
+/// ManifestEntry is a line of a manifest, whose path has
+/// been converted to the path format of an EDList.
+pub(super) struct ManifestEntry {
+	pub line:     usize,
+	pub checksum: FileChecksum,
+	pub path:     String,
+}
+
+/// Formats a checksum and a path as a line of a manifest, in the
+/// format used by the checksum programs from coreutils, like sha256sum and b2sum.
+///
+/// The line contains a lowercase hexadecimal checksum, two spaces and the path.
+/// If the path contains a backslash or a line break, the line starts
+/// with a backslash, and those characters are escaped in the path.
+pub(super) fn format_line(checksum: &FileChecksum, path: &str) -> String {
+	if path.contains(['\\', '\n', '\r']) {
+		let escaped_path = path.replace('\\', r"\\").replace('\n', r"\n").replace('\r', r"\r");
+		format!("\\{}  {}\n", hex::encode(checksum.as_ref()), escaped_path)
+	}
+	else {
+		format!("{}  {}\n", hex::encode(checksum.as_ref()), path)
+	}
+}
+
+/// Opens the manifest at manifest_path, and reads every line of it, like read_manifest.
+pub(super) fn read_manifest_file(manifest_path: &str) -> Result<(Vec<ManifestEntry>, Vec<ManifestLineError>), ReadManifestError> {
+	let file = File::open(manifest_path).map_err(|err| ReadManifestError::OpenError(manifest_path.to_string(), err))?;
+	read_manifest(BufReader::new(file)).map_err(|err| ReadManifestError::IoError(manifest_path.to_string(), err))
+}
+
+/// Reads every line of a manifest.
+///
+/// Returns the lines that could be interpreted, together
+/// with an error for every line that could not.
+pub(super) fn read_manifest(reader: impl BufRead) -> Result<(Vec<ManifestEntry>, Vec<ManifestLineError>), std::io::Error> {
+	let mut entries = Vec::new();
+	let mut errors = Vec::new();
+	for (index, line) in reader.lines().enumerate() {
+		let line = line?;
+		if line.is_empty() {
+			continue;
+		}
+		match parse_line(&line, index + 1) {
+			Ok(entry) => entries.push(entry),
+			Err(err) => errors.push(err),
+		}
+	}
+	Ok((entries, errors))
+}
+
+fn parse_line(line: &str, line_number: usize) -> Result<ManifestEntry, ManifestLineError> {
+	let (escaped, line) = match line.strip_prefix('\\') {
+		Some(line) => (true, line),
+		None => (false, line),
+	};
+	// The checksum is followed by a space, and a space or a star for binary mode.
+	let (checksum_string, path) = line.split_once(' ').ok_or(ManifestLineError::UnparsableLine(line_number))?;
+	let path = path.strip_prefix([' ', '*']).ok_or(ManifestLineError::UnparsableLine(line_number))?;
+	let checksum = FileChecksum::from_hex(checksum_string).map_err(|_| ManifestLineError::UnparsableLine(line_number))?;
+	if checksum.is_empty() || path.is_empty() {
+		return Err(ManifestLineError::UnparsableLine(line_number));
+	}
+
+	let path = if escaped {
+		unescape_path(path).ok_or(ManifestLineError::UnparsableLine(line_number))?
+	}
+	else {
+		path.to_string()
+	};
+	let path = to_list_path(&path).ok_or(ManifestLineError::UnsupportedPath(line_number, path))?;
+	Ok(ManifestEntry { line: line_number, checksum, path })
+}
+
+fn unescape_path(path: &str) -> Option<String> {
+	let mut unescaped = String::with_capacity(path.len());
+	let mut chars = path.chars();
+	while let Some(character) = chars.next() {
+		if character == '\\' {
+			match chars.next()? {
+				'\\' => unescaped.push('\\'),
+				'n' => unescaped.push('\n'),
+				'r' => unescaped.push('\r'),
+				_ => return None,
+			}
+		}
+		else {
+			unescaped.push(character);
+		}
+	}
+	Some(unescaped)
+}
+
+/// Converts a path relative to the root directory, into the
+/// "./" prefixed format used by EDList.
+/// Absolute paths, and paths leaving the root directory, can't be converted.
+fn to_list_path(path: &str) -> Option<String> {
+	if path.starts_with('/') || path.split('/').any(|component| component == "..") {
+		return None;
+	}
+	let path = path.strip_prefix("./").unwrap_or(path);
+	Some(format!("./{}", path))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn formatted_lines_are_read_back() {
+		let checksum = FileChecksum::from_slice(&[0xAB; 32]);
+		let paths = ["./plain", "./with space", "./back\\slash", "./line\nbreak"];
+		let manifest: String = paths.iter().map(|path| format_line(&checksum, path)).collect();
+
+		let (entries, errors) = read_manifest(manifest.as_bytes()).unwrap();
+		assert!(errors.is_empty());
+		assert_eq!(entries.iter().map(|entry| entry.path.as_str()).collect::<Vec<_>>(), paths);
+		assert!(entries.iter().all(|entry| entry.checksum == checksum));
+		assert_eq!(entries.iter().map(|entry| entry.line).collect::<Vec<_>>(), [1, 2, 3, 4]);
+	}
+
+	#[test]
+	fn lines_of_the_coreutils_formats_are_read() {
+		let manifest = "abcd  file\nabcd *binary/file\n\nabcd  ./dotted\n";
+		let (entries, errors) = read_manifest(manifest.as_bytes()).unwrap();
+		assert!(errors.is_empty());
+		let paths: Vec<_> = entries.iter().map(|entry| entry.path.as_str()).collect();
+		assert_eq!(paths, ["./file", "./binary/file", "./dotted"]);
+		assert_eq!(entries.iter().map(|entry| entry.line).collect::<Vec<_>>(), [1, 2, 4]);
+	}
+
+	#[test]
+	fn invalid_lines_are_reported() {
+		let manifest = "nothex  file\nabcd\nabcd  /absolute\nabcd  ../outside\n\\abcd  bad\\escape\n";
+		let (entries, errors) = read_manifest(manifest.as_bytes()).unwrap();
+		assert!(entries.is_empty());
+		let kinds: Vec<_> = errors.iter().map(|err| (err.kind(), err.line())).collect();
+		assert_eq!(kinds, [
+			("UnparsableLine", 1),
+			("UnparsableLine", 2),
+			("UnsupportedPath", 3),
+			("UnsupportedPath", 4),
+			("UnparsableLine", 5)
+		]);
+	}
+}
@@ -0,0 +1,97 @@
+/*
+	This file is part of file_hasher.
+
+	file_hasher is free software: you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation, either version 3 of the License, or
+	(at your option) any later version.
+
+	file_hasher is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License
+	along with file_hasher.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use super::e_d_element::errors::EDElementError;
+use crate::shared::FileChecksum;
+
+/// DuplicateGroup is a group of two or more elements in a list,
+/// that are either links with the same target path,
+/// or files with the same checksum.
+///
+/// The paths are in the order of the list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DuplicateGroup {
+	Links { target: String, paths: Vec<String> },
+	Files { checksum: FileChecksum, paths: Vec<String> },
+}
+impl DuplicateGroup {
+	pub fn paths(&self) -> &[String] {
+		match self {
+			DuplicateGroup::Links { paths, .. } | DuplicateGroup::Files { paths, .. } => paths,
+		}
+	}
+}
+
+/// DeletionReason is the reason an element is a candidate for deletion,
+/// either its path is excluded by the banlist or an ignore file, or its metadata can't be verified,
+/// which usually means that the file no longer exists.
+#[derive(Debug)]
+pub enum DeletionReason {
+	InBanlist,
+	MetadataError(EDElementError),
+}
+
+/// DeletionCandidate is an element of a list,
+/// that can be deleted from the list using EDList::delete_paths.
+#[derive(Debug)]
+pub struct DeletionCandidate {
+	path:   String,
+	reason: DeletionReason,
+}
+impl DeletionCandidate {
+	pub(super) fn new(path: String, reason: DeletionReason) -> DeletionCandidate {
+		DeletionCandidate { path, reason }
+	}
+
+	pub fn get_path(&self) -> &str {
+		&self.path
+	}
+
+	pub fn get_reason(&self) -> &DeletionReason {
+		&self.reason
+	}
+}
+impl std::fmt::Display for DeletionCandidate {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match &self.reason {
+			DeletionReason::InBanlist => write!(f, "Path {} is excluded by the banlist or an ignore file", self.path),
+			DeletionReason::MetadataError(err) => write!(f, "{}", err),
+		}
+	}
+}
+
+/// BannedGroup is the elements of a list, that are excluded by the same rule of the banlist,
+/// or of a .file_hasher_ignore file, in which case the rule is prefixed by the path of the ignore file.
+///
+/// The paths are in the order of the list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BannedGroup {
+	rule:  String,
+	paths: Vec<String>,
+}
+impl BannedGroup {
+	pub(super) fn new(rule: String, paths: Vec<String>) -> BannedGroup {
+		BannedGroup { rule, paths }
+	}
+
+	pub fn get_rule(&self) -> &str {
+		&self.rule
+	}
+
+	pub fn paths(&self) -> &[String] {
+		&self.paths
+	}
+}
@@ -0,0 +1,92 @@
+/*
+	This file is part of file_hasher.
+
+	file_hasher is free software: you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation, either version 3 of the License, or
+	(at your option) any later version.
+
+	file_hasher is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License
+	along with file_hasher.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use std::fs::canonicalize;
+
+use super::e_d_element::EDElement;
+use crate::shared::Checksum;
+
+/// FileOperation is a single operation on the filesystem,
+/// that is done when a SyncPlan is applied.
+///
+/// Deleted files are moved to the sync backup folder,
+/// instead of being removed.
+#[derive(Debug)]
+pub enum FileOperation {
+	Delete(String),
+	Move { from: String, to: String },
+	Copy { from: String, to: String },
+}
+impl std::fmt::Display for FileOperation {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		use FileOperation::*;
+		let from_convert = |from| {
+			canonicalize(from)
+				.map(|from| from.to_str().unwrap().to_string())
+				.unwrap_or(format!("'''Error getting canonical path of {}'''", from))
+		};
+
+		match self {
+			Delete(path) => write!(f, "Delete {}", path),
+			Move { from, to } => write!(f, "Move {} to {}", from_convert(from), to),
+			Copy { from, to } => write!(f, "Copy {} to {}", from_convert(from), to),
+		}
+	}
+}
+
+/// SyncPlan contains the file operations needed to sync a relative path
+/// of another EDList to a relative path of this EDList,
+/// together with the element list this EDList will have after the sync.
+///
+/// It is created by EDList::plan_sync, and the filesystem is only modified
+/// once it is given to EDList::apply_sync_plan.
+/// The pre_file_operations are done before the post_file_operations,
+/// such that files can be moved away before they are overwritten.
+///
+/// The base_xor_checksum and base_length are the xor_checksum and
+/// the length of the list the plan was made from, a plan can only
+/// be applied to an unchanged list.
+#[derive(Debug)]
+pub struct SyncPlan {
+	pub(super) source_folder_path:   String,
+	pub(super) sync_to_prefix:       String,
+	pub(super) sync_from_prefix:     String,
+	pub(super) pre_file_operations:  Vec<FileOperation>,
+	pub(super) post_file_operations: Vec<FileOperation>,
+	pub(super) files_moved:          bool,
+	pub(super) element_list:         Vec<EDElement>,
+	pub(super) xor_checksum:         Checksum,
+	pub(super) base_xor_checksum:    Checksum,
+	pub(super) base_length:          usize,
+}
+impl SyncPlan {
+	pub fn get_source_folder_path(&self) -> &str {
+		&self.source_folder_path
+	}
+
+	pub fn get_sync_to_prefix(&self) -> &str {
+		&self.sync_to_prefix
+	}
+
+	pub fn get_sync_from_prefix(&self) -> &str {
+		&self.sync_from_prefix
+	}
+
+	/// Returns the file operations in the order they will be done.
+	pub fn get_operations(&self) -> impl Iterator<Item = &FileOperation> {
+		self.pre_file_operations.iter().chain(self.post_file_operations.iter())
+	}
+}
@@ -19,4 +19,7 @@
 pub mod e_d_list;
 pub mod path_banlist;
 mod shared;
-pub use shared::{AnyString, InterfacerReturnType, SlashEnding, UserInterface, YesNo, YesNoAuto};
+pub use shared::{
+	AnyString, Checksum, FileChecksum, HashAlgorithm, InterfacerReturnType, ProgressEvent, ProgressPhase, SlashEnding, UserInterface,
+	YesNo, YesNoAuto,
+};
@@ -18,7 +18,8 @@
 use std::{
 	collections::HashMap,
 	fs::{create_dir_all, File},
-	io::{BufRead, BufReader, Write},
+	io::{BufRead, BufReader},
+	path::Path,
 };
 
 use blake2::{
@@ -28,16 +29,28 @@ use blake2::{
 
 use crate::{
 	shared,
-	shared::{constants, UserInterface, YesNo},
+	shared::{constants, Checksum, UserInterface, YesNo},
 };
 
+mod editing;
 pub mod errors;
+mod pattern;
+mod rule;
 use errors::*;
+use pattern::{GLOB_PREFIX, REGEX_PREFIX};
+use rule::{BanRule, NEGATION_PREFIX};
+
+/// The path of the banlist file.
+const BANLIST_PATH: &str = "./file_hasher_files/banlist";
+/// The path of the copy of the banlist, that was written the last time the banlist was sealed.
+const SEALED_BANLIST_PATH: &str = "./file_hasher_files/banlist.sealed";
 
 enum LineType<'a> {
 	Comment,
 	Checksum(&'a str),
 	BannedPath(&'a str),
+	BannedPattern(&'a str),
+	Negation(&'a str),
 }
 
 #[derive(Debug, Clone)]
@@ -48,9 +61,18 @@ enum CharMapper {
 
 /// PathBanlist is a HashSet that contains all the paths that
 /// should not be hashed by the EDList objects.
+///
+/// Every line of the banlist is stored in rules, in the order of the file.
+/// Literal paths are also stored in the banned_paths trie.
+///
+/// If the banlist has no negation lines, a path is banned if a prefix of it
+/// is in the trie, or if any pattern matches it.
+/// Otherwise the last rule that matches the path decides.
 #[derive(Debug, Clone)]
 pub struct PathBanlist {
-	banned_paths: HashMap<char, CharMapper>,
+	banned_paths:  HashMap<char, CharMapper>,
+	rules:         Vec<BanRule>,
+	has_negations: bool,
 }
 impl PathBanlist {
 	/// Requires an object implementing the trait UserInterface also defined in
@@ -61,7 +83,7 @@ impl PathBanlist {
 	/// If attempts go wrong, the funtion will return a string, with a
 	/// description of the problem.
 	pub fn open(banlist_interfacer: &impl UserInterface) -> Result<PathBanlist, OpenPathBanlistError> {
-		let file = match File::open("./file_hasher_files/banlist") {
+		let file = match File::open(BANLIST_PATH) {
 			Ok(file) => file,
 			Err(err) => loop {
 				let create_new: YesNo = banlist_interfacer.get_user_answer(&format!(
@@ -77,35 +99,35 @@ impl PathBanlist {
 				}
 			},
 		};
-		let buf_reader = BufReader::new(file);
+		let lines = BufReader::new(file).lines().collect::<Result<Vec<_>, _>>()?;
+		PathBanlist::from_lines(&lines)
+	}
 
-		let mut hasher = Blake2bVar::new(constants::HASH_OUTPUT_LENGTH).unwrap();
-		let mut file_checksum: Option<String> = Option::None;
-		let mut banned_paths: HashMap<char, CharMapper> = HashMap::new();
+	/// Creates a PathBanlist from the contents of a .file_hasher_ignore file.
+	/// Ignore files use the syntax of the banlist, but have no checksum line,
+	/// since their integrity is protected by the checksum stored in file_hashes.
+	pub(crate) fn from_ignore_file(contents: &str) -> Result<PathBanlist, OpenPathBanlistError> {
+		let lines: Vec<String> = contents.lines().map(String::from).collect();
+		let (banlist, _) = PathBanlist::parse_lines(&lines)?;
+		Ok(banlist)
+	}
 
-		for line in buf_reader.lines() {
-			match PathBanlist::identify_line(&line?) {
-				LineType::BannedPath(line) => {
-					hasher.update(line.as_bytes());
+	/// Returns the lines of the rules of the banlist, in the order of the banlist file.
+	pub fn get_rules(&self) -> impl Iterator<Item = &str> {
+		self.rules.iter().map(BanRule::get_line)
+	}
 
-					PathBanlist::insert_to_banlist(line.chars(), &mut banned_paths);
-				},
-				LineType::Checksum(value) => match file_checksum {
-					None => file_checksum = Some(value.to_string()),
-					Some(_val) => {
-						return Err(OpenPathBanlistError::DuplicateChecksum);
-					},
-				},
-				LineType::Comment => (), // Comments are not important to the integrity of the file...
-			}
-		}
+	/// Interprets the lines of a banlist file,
+	/// and verifies them against the checksum line of the file.
+	fn from_lines(lines: &[String]) -> Result<PathBanlist, OpenPathBanlistError> {
+		let (banlist, file_checksum) = PathBanlist::parse_lines(lines)?;
 
 		// Verify checksum validiy against the generated hash.
-		let generated_checksum = shared::blake2_to_checksum(hasher);
+		let generated_checksum = PathBanlist::generate_checksum(lines);
 		match file_checksum {
 			Some(checksum) => {
 				if generated_checksum.to_string() == checksum {
-					Ok(PathBanlist { banned_paths })
+					Ok(banlist)
 				}
 				else {
 					Err(OpenPathBanlistError::InvalidChecksum(generated_checksum))
@@ -115,6 +137,63 @@ impl PathBanlist {
 		}
 	}
 
+	/// Used internally to interpret the lines of a banlist.
+	/// Returns the banlist and the checksum stored in the lines, if any.
+	fn parse_lines(lines: &[String]) -> Result<(PathBanlist, Option<&str>), OpenPathBanlistError> {
+		let mut file_checksum: Option<&str> = Option::None;
+		let mut banned_paths: HashMap<char, CharMapper> = HashMap::new();
+		let mut rules: Vec<BanRule> = Vec::new();
+
+		for line in lines {
+			match PathBanlist::identify_line(line) {
+				LineType::BannedPath(line) => {
+					PathBanlist::insert_to_banlist(line.chars(), &mut banned_paths);
+					rules.push(BanRule::parse(line)?);
+				},
+				LineType::BannedPattern(line) | LineType::Negation(line) => rules.push(BanRule::parse(line)?),
+				LineType::Checksum(value) => match file_checksum {
+					None => file_checksum = Some(value),
+					Some(_val) => {
+						return Err(OpenPathBanlistError::DuplicateChecksum);
+					},
+				},
+				LineType::Comment => (),
+			}
+		}
+
+		let has_negations = rules.iter().any(BanRule::is_negated);
+		Ok((PathBanlist { banned_paths, rules, has_negations }, file_checksum))
+	}
+
+	/// Generates the checksum of the lines of a banlist, from all lines except comments and checksums.
+	fn generate_checksum(lines: &[String]) -> Checksum {
+		let mut hasher = Blake2bVar::new(constants::HASH_OUTPUT_LENGTH).unwrap();
+		for line in lines {
+			match PathBanlist::identify_line(line) {
+				LineType::BannedPath(_) | LineType::BannedPattern(_) | LineType::Negation(_) => hasher.update(line.as_bytes()),
+				// Comments are not important to the integrity of the file...
+				LineType::Checksum(_) | LineType::Comment => (),
+			}
+		}
+		shared::blake2_to_checksum(hasher)
+	}
+
+	/// Writes the lines to the banlist file without their checksum lines,
+	/// followed by a checksum line generated from them.
+	///
+	/// The written banlist is also stored as the last sealed version of the banlist,
+	/// which the banlist is compared to, when it is resealed after being edited.
+	fn write_sealed(lines: &[String]) -> std::io::Result<()> {
+		let mut contents = String::new();
+		for line in lines.iter().filter(|line| !PathBanlist::is_checksum_line(line)) {
+			contents.push_str(&format!("{}\n", line));
+		}
+		contents.push_str(&format!("{}{}\n", constants::FIN_CHECKSUM_PREFIX, PathBanlist::generate_checksum(lines)));
+
+		shared::write_file_atomically(Path::new(BANLIST_PATH), contents.as_bytes())?;
+		shared::write_file_atomically(Path::new(SEALED_BANLIST_PATH), contents.as_bytes())
+	}
+
 	/// Attempts to create a new banlist file.
 	/// Requires a object that implements UserInterface, so that it can send it
 	/// on to the open function.
@@ -122,22 +201,12 @@ impl PathBanlist {
 	/// the error.
 	fn create() -> Result<(), NewPathBanlistError> {
 		create_dir_all("./file_hasher_files").map_err(NewPathBanlistError::CreatingFileHasherDir)?;
-		let mut file = File::create("./file_hasher_files/banlist").map_err(NewPathBanlistError::CreatingBanlist)?;
 
-		let mut hasher = Blake2bVar::new(constants::HASH_OUTPUT_LENGTH).unwrap();
-		let def_banned_list = ["./lost+found", "./.Trash-1000/", "./file_hasher_files/"];
-
-		for string in def_banned_list.iter() {
-			file.write(format!("{}\n", string).as_bytes()).map_err(NewPathBanlistError::WriteFileError)?;
-			hasher.update(string.as_bytes());
-		}
-
-		file.write(format!("{}{}", constants::FIN_CHECKSUM_PREFIX, shared::blake2_to_checksum(hasher)).as_bytes())
-			.map_err(NewPathBanlistError::WriteFileError)?;
-		Ok(())
+		let def_banned_list = ["./lost+found", "glob:.Trash-*/", "./file_hasher_files/"].map(String::from);
+		PathBanlist::write_sealed(&def_banned_list).map_err(NewPathBanlistError::WriteFileError)
 	}
 
-	/// identify_line determines if a line is a comment, a checksum or a banned path.
+	/// identify_line determines if a line is a comment, a checksum, a negation, a banned pattern or a banned path.
 	fn identify_line(line: &str) -> LineType {
 		match line.chars().next() {
 			Some(character) => {
@@ -154,10 +223,21 @@ impl PathBanlist {
 			return LineType::Checksum(checksum);
 		}
 
-		// If line is not identified as a comment or a checksum, it must be a bannedpath.
+		if line.starts_with(NEGATION_PREFIX) {
+			return LineType::Negation(line);
+		}
+		if line.starts_with(GLOB_PREFIX) || line.starts_with(REGEX_PREFIX) {
+			return LineType::BannedPattern(line);
+		}
+
+		// If line is not identified as a comment, a checksum, a negation or a pattern, it must be a bannedpath.
 		LineType::BannedPath(line)
 	}
 
+	fn is_checksum_line(line: &str) -> bool {
+		matches!(PathBanlist::identify_line(line), LineType::Checksum(_))
+	}
+
 	/// Used internally by the path_banlist open constructor,
 	/// to insert the needed paths into the banlist.
 	///
@@ -200,10 +280,63 @@ impl PathBanlist {
 	}
 
 	/// Used to test whether the given path has any
-	/// of its prefixes defined in the banlist.
-	/// Returns true, if there is such a prefix, else it
+	/// of its prefixes defined in the banlist,
+	/// or is matched by any of the patterns in the banlist.
+	/// Returns true, if there is such a prefix or pattern,
+	/// and it isn't overruled by a later negation line, else it
 	/// returns false.
+	///
+	/// Paths of directories must end with a forward slash,
+	/// for patterns that only match directories to match them.
 	pub fn is_in_banlist(&self, path: &str) -> bool {
+		self.decision(path) == Some(true)
+	}
+
+	/// Returns Some(true) if the given path is banned, Some(false) if it is unbanned
+	/// by a negation line, or None if no rule of the banlist matches the path.
+	/// Ignore files use this to decide, whether a rule of an outer scope is overruled.
+	pub(crate) fn decision(&self, path: &str) -> Option<bool> {
+		if !self.has_negations {
+			let is_banned = self.is_prefix_in_banlist(path) || self.rules.iter().any(|rule| rule.is_pattern() && rule.is_match(path));
+			return is_banned.then_some(true);
+		}
+		self.deciding_rule(path).map(|(_, is_banned)| is_banned)
+	}
+
+	/// Returns the line of the banlist rule, that bans the given path,
+	/// or None if the path isn't in the banlist.
+	/// Like in is_in_banlist, this is the last rule that matches the path.
+	pub fn matching_rule(&self, path: &str) -> Option<&str> {
+		self.deciding_rule(path).filter(|(_, is_banned)| *is_banned).map(|(line, _)| line)
+	}
+
+	/// Returns the line of the last rule that matches the given path, and whether it bans the path,
+	/// or None if no rule matches the path.
+	pub(crate) fn deciding_rule(&self, path: &str) -> Option<(&str, bool)> {
+		self.rules
+			.iter()
+			.rev()
+			.find(|rule| rule.is_match(path))
+			.map(|rule| (rule.get_line(), !rule.is_negated()))
+	}
+
+	/// Used to test whether the given directory, and everything within it, is banned.
+	/// A banned directory may still contain paths that are unbanned by negation lines,
+	/// such directories must be searched, and each path within them tested using is_in_banlist.
+	///
+	/// The path of the directory must end with a forward slash.
+	pub fn is_directory_in_banlist(&self, path: &str) -> bool {
+		self.is_in_banlist(path) && !self.may_unban_within(path)
+	}
+
+	/// Returns true if a negation line of the banlist may unban a path within the given directory.
+	pub(crate) fn may_unban_within(&self, directory: &str) -> bool {
+		self.rules.iter().any(|rule| rule.is_negated() && rule.may_match_within(directory))
+	}
+
+	/// Used to test whether the given path has any
+	/// of its prefixes defined in the literal paths of the banlist.
+	fn is_prefix_in_banlist(&self, path: &str) -> bool {
 		let mut hashmap = &self.banned_paths;
 		for character in path.chars() {
 			match hashmap.get(&character) {
@@ -217,6 +350,6 @@ impl PathBanlist {
 
 	/// Creates a PathBanlist without a backing file.
 	pub(crate) fn new_dummy() -> PathBanlist {
-		PathBanlist { banned_paths: HashMap::new() }
+		PathBanlist { banned_paths: HashMap::new(), rules: Vec::new(), has_negations: false }
 	}
 }
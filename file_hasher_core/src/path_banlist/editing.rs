@@ -0,0 +1,119 @@
+/*
+	This file is part of file_hasher.
+
+	file_hasher is free software: you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation, either version 3 of the License, or
+	(at your option) any later version.
+
+	file_hasher is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License
+	along with file_hasher.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::fs;
+
+use super::{
+	errors::*,
+	pattern::{GLOB_PREFIX, REGEX_PREFIX},
+	rule::NEGATION_PREFIX,
+	PathBanlist, BANLIST_PATH, SEALED_BANLIST_PATH,
+};
+use crate::shared::{self, UserInterface, YesNo};
+
+impl PathBanlist {
+	/// Adds a rule to the end of the banlist file, and seals the banlist with a new checksum.
+	///
+	/// The banlist must be valid before the rule is added,
+	/// such that edits by hand are never sealed without being shown to the user, see reseal.
+	pub fn add_rule(rule: &str) -> Result<(), BanlistEditError> {
+		let rule_without_negation = rule.strip_prefix(NEGATION_PREFIX).unwrap_or(rule);
+		if !["./", GLOB_PREFIX, REGEX_PREFIX].iter().any(|prefix| rule_without_negation.starts_with(prefix)) {
+			return Err(BanlistEditError::InvalidRule(rule.to_string()));
+		}
+		let (mut lines, banlist) = PathBanlist::read_verified_lines()?;
+		if banlist.get_rules().any(|existing_rule| existing_rule == rule) {
+			return Err(BanlistEditError::DuplicateRule(rule.to_string()));
+		}
+		lines.push(rule.to_string());
+		// Patterns are validated before the banlist is written.
+		PathBanlist::parse_lines(&lines)?;
+		Ok(PathBanlist::write_sealed(&lines)?)
+	}
+
+	/// Removes every line of the banlist file containing the rule,
+	/// and seals the banlist with a new checksum.
+	///
+	/// Like for add_rule, the banlist must be valid before the rule is removed.
+	pub fn remove_rule(rule: &str) -> Result<(), BanlistEditError> {
+		let (mut lines, banlist) = PathBanlist::read_verified_lines()?;
+		if !banlist.get_rules().any(|existing_rule| existing_rule == rule) {
+			return Err(BanlistEditError::RuleNotFound(rule.to_string()));
+		}
+		lines.retain(|line| line != rule);
+		Ok(PathBanlist::write_sealed(&lines)?)
+	}
+
+	/// Seals a banlist, that has been edited by hand, with a new checksum.
+	///
+	/// The user is shown how the banlist differs from the last sealed version,
+	/// and asked whether the banlist should be sealed as it is now.
+	/// If the checksum of the banlist is already valid, nothing is changed.
+	pub fn reseal(user_interface: &impl UserInterface) -> Result<(), BanlistEditError> {
+		let lines = PathBanlist::read_lines(BANLIST_PATH)?;
+		let unsealed_lines: Vec<String> = lines.iter().filter(|line| !PathBanlist::is_checksum_line(line)).cloned().collect();
+		// Rules that can't be parsed are never sealed.
+		PathBanlist::parse_lines(&unsealed_lines)?;
+		if PathBanlist::from_lines(&lines).is_ok() {
+			user_interface.send_message("The checksum of the banlist is valid, the banlist is already sealed");
+			return Ok(());
+		}
+
+		// The sealed version is only shown, if it hasn't been changed since it was sealed.
+		let sealed_lines = PathBanlist::read_lines(SEALED_BANLIST_PATH)
+			.ok()
+			.filter(|lines| PathBanlist::from_lines(lines).is_ok());
+		let description = match sealed_lines {
+			Some(sealed_lines) => {
+				let sealed_lines: Vec<_> = sealed_lines.into_iter().filter(|line| !PathBanlist::is_checksum_line(line)).collect();
+				format!(
+					"The banlist differs from the last sealed version.\n{}",
+					shared::describe_differences(
+						&unsealed_lines.join("\n"),
+						"the banlist",
+						&sealed_lines.join("\n"),
+						"the last sealed version"
+					)
+				)
+			},
+			None => format!(
+				"There is no valid sealed version of the banlist to compare with, the banlist is:\n{}",
+				unsealed_lines.join("\n")
+			),
+		};
+		user_interface.send_message(&description);
+
+		let answer: YesNo = user_interface.get_user_answer("Do you wish to seal the banlist as it is now?");
+		if answer == YesNo::No {
+			return Err(BanlistEditError::UserDeniedReseal);
+		}
+		PathBanlist::write_sealed(&unsealed_lines)?;
+		user_interface.send_message("The banlist has been sealed");
+		Ok(())
+	}
+
+	/// Reads the lines of the banlist file, and verifies them against its checksum.
+	fn read_verified_lines() -> Result<(Vec<String>, PathBanlist), BanlistEditError> {
+		let lines = PathBanlist::read_lines(BANLIST_PATH)?;
+		let banlist = PathBanlist::from_lines(&lines)?;
+		Ok((lines, banlist))
+	}
+
+	fn read_lines(path: &str) -> std::io::Result<Vec<String>> {
+		Ok(fs::read_to_string(path)?.lines().map(String::from).collect())
+	}
+}
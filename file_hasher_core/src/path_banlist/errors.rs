@@ -10,6 +10,7 @@ pub enum OpenPathBanlistError {
 	IOError(std::io::Error),
 	InvalidChecksum(Checksum),
 	MissingChecksum(Checksum),
+	InvalidPattern(String, String),
 }
 impl std::error::Error for OpenPathBanlistError {}
 impl std::fmt::Display for OpenPathBanlistError {
@@ -23,17 +24,18 @@ impl std::fmt::Display for OpenPathBanlistError {
 			InvalidChecksum(hash_string) => write!(
 				f,
 				"Checksum for banlist is invalid.\nIf the current banlist is correct,\nReplace the checksum in the banlist file with the \
-				 following:\n{}{}",
+				 following:\n{}{}\nOr seal the banlist using the banlist reseal operation, which shows how it was changed.",
 				constants::FIN_CHECKSUM_PREFIX,
 				hash_string
 			),
 			MissingChecksum(hash_string) => write!(
 				f,
 				"There is no checksum in the banlist file.\nIf the current banlist is correct,\nType the following line into the banlist \
-				 file:\n{}{}",
+				 file:\n{}{}\nOr seal the banlist using the banlist reseal operation, which shows how it was changed.",
 				constants::FIN_CHECKSUM_PREFIX,
 				hash_string
 			),
+			InvalidPattern(line, err) => write!(f, "Invalid pattern in banlist line \"{}\", err = {}", line, err),
 		}
 	}
 }
@@ -52,7 +54,6 @@ impl From<NewPathBanlistError> for OpenPathBanlistError {
 pub enum NewPathBanlistError {
 	UserDeniedNewList,
 	CreatingFileHasherDir(std::io::Error),
-	CreatingBanlist(std::io::Error),
 	WriteFileError(std::io::Error),
 }
 impl std::error::Error for NewPathBanlistError {}
@@ -62,8 +63,48 @@ impl std::fmt::Display for NewPathBanlistError {
 		match self {
 			UserDeniedNewList => write!(f, "New banlist file could not be created due to user choice"),
 			CreatingFileHasherDir(err) => write!(f, "Error creating file_hasher directory, Error = {}", err),
-			CreatingBanlist(err) => write!(f, "Error creating file, Error = {}", err),
 			WriteFileError(err) => write!(f, "Error writing to file, Error = {}", err),
 		}
 	}
 }
+
+#[derive(Debug)]
+pub enum BanlistEditError {
+	OpenPathBanlistError(OpenPathBanlistError),
+	IOError(std::io::Error),
+	InvalidRule(String),
+	DuplicateRule(String),
+	RuleNotFound(String),
+	UserDeniedReseal,
+}
+impl std::error::Error for BanlistEditError {}
+impl std::fmt::Display for BanlistEditError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		use BanlistEditError::*;
+		match self {
+			OpenPathBanlistError(err) => write!(f, "Error in banlist: {}", err),
+			IOError(err) => write!(f, "Error reading or writing the banlist, IOError: {}", err),
+			InvalidRule(rule) => write!(
+				f,
+				"\"{}\" is not a banlist rule, rules start with \"./\", \"{}\" or \"{}\", and may be negated by a leading \"{}\"",
+				rule,
+				pattern::GLOB_PREFIX,
+				pattern::REGEX_PREFIX,
+				rule::NEGATION_PREFIX
+			),
+			DuplicateRule(rule) => write!(f, "\"{}\" is already a rule in the banlist", rule),
+			RuleNotFound(rule) => write!(f, "\"{}\" is not a rule in the banlist", rule),
+			UserDeniedReseal => write!(f, "The banlist was not sealed due to user choice"),
+		}
+	}
+}
+impl From<OpenPathBanlistError> for BanlistEditError {
+	fn from(err: OpenPathBanlistError) -> BanlistEditError {
+		BanlistEditError::OpenPathBanlistError(err)
+	}
+}
+impl From<std::io::Error> for BanlistEditError {
+	fn from(err: std::io::Error) -> BanlistEditError {
+		BanlistEditError::IOError(err)
+	}
+}
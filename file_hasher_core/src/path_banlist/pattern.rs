@@ -0,0 +1,94 @@
+/*
+	This file is part of file_hasher.
+
+	file_hasher is free software: you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation, either version 3 of the License, or
+	(at your option) any later version.
+
+	file_hasher is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License
+	along with file_hasher.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use globset::{GlobBuilder, GlobMatcher};
+use regex::Regex;
+
+use super::errors::OpenPathBanlistError;
+
+/// The prefix of banlist lines containing a glob pattern.
+pub(super) const GLOB_PREFIX: &str = "glob:";
+/// The prefix of banlist lines containing a regular expression.
+pub(super) const REGEX_PREFIX: &str = "regex:";
+
+#[derive(Debug, Clone)]
+enum PatternMatcher {
+	Glob(GlobMatcher),
+	Regex(Regex),
+}
+
+/// BanPattern is a glob or regex line of the banlist.
+///
+/// Globs are matched against paths without the leading "./",
+/// the path of a file is banned when the glob matches the path,
+/// or the path of one of the directories that contain it.
+/// `*` and `?` never match a `/`, while `**` matches any amount of directories.
+/// A glob without a `/` matches names at any depth, like `*.tmp`,
+/// and a glob ending with a `/` only matches directories, like `**/node_modules/`.
+///
+/// Regexes are matched against the whole path, including the leading "./",
+/// and the paths of the directories that contain it, which end with a `/`.
+#[derive(Debug, Clone)]
+pub(super) struct BanPattern {
+	matcher:        PatternMatcher,
+	directory_only: bool,
+}
+impl BanPattern {
+	/// Parses a banlist line starting with GLOB_PREFIX or REGEX_PREFIX.
+	pub(super) fn parse(line: &str) -> Result<BanPattern, OpenPathBanlistError> {
+		let invalid_pattern = |err: String| OpenPathBanlistError::InvalidPattern(line.to_string(), err);
+		if let Some(regex) = line.strip_prefix(REGEX_PREFIX) {
+			let regex = Regex::new(regex).map_err(|err| invalid_pattern(err.to_string()))?;
+			return Ok(BanPattern { matcher: PatternMatcher::Regex(regex), directory_only: false });
+		}
+
+		let glob = line.strip_prefix(GLOB_PREFIX).unwrap_or(line);
+		let glob = glob.strip_prefix("./").unwrap_or(glob);
+		let directory_only = glob.ends_with('/');
+		let glob = glob.trim_end_matches('/');
+		let glob = if glob.contains('/') { glob.to_string() } else { format!("**/{}", glob) };
+		let matcher = GlobBuilder::new(&glob)
+			.literal_separator(true)
+			.build()
+			.map_err(|err| invalid_pattern(err.to_string()))?;
+		Ok(BanPattern { matcher: PatternMatcher::Glob(matcher.compile_matcher()), directory_only })
+	}
+
+	/// Returns true if the pattern matches the path,
+	/// paths of directories must end with a `/`.
+	pub(super) fn is_match(&self, path: &str) -> bool {
+		match &self.matcher {
+			PatternMatcher::Regex(regex) => path
+				.match_indices('/')
+				.map(|(index, _)| &path[..=index])
+				.filter(|directory| *directory != "./")
+				.chain(std::iter::once(path))
+				.any(|candidate| regex.is_match(candidate)),
+			PatternMatcher::Glob(glob) => {
+				let path = path.strip_prefix("./").unwrap_or(path);
+				let is_directory = path.ends_with('/');
+				let path = path.trim_end_matches('/');
+				let mut directories = path.match_indices('/').map(|(index, _)| &path[..index]);
+				if self.directory_only && !is_directory {
+					directories.any(|directory| glob.is_match(directory))
+				}
+				else {
+					directories.chain(std::iter::once(path)).any(|candidate| glob.is_match(candidate))
+				}
+			},
+		}
+	}
+}
@@ -0,0 +1,86 @@
+/*
+	This file is part of file_hasher.
+
+	file_hasher is free software: you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation, either version 3 of the License, or
+	(at your option) any later version.
+
+	file_hasher is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License
+	along with file_hasher.  If not, see <https://www.gnu.org/licenses/>.
+*/
+use super::{
+	errors::OpenPathBanlistError,
+	pattern::{BanPattern, GLOB_PREFIX, REGEX_PREFIX},
+};
+
+/// The prefix of banlist lines, that unban the paths they match.
+pub(super) const NEGATION_PREFIX: char = '!';
+
+#[derive(Debug, Clone)]
+enum RuleMatcher {
+	Prefix(String),
+	Pattern(BanPattern),
+}
+
+/// BanRule is a line of the banlist, that either bans the paths it matches,
+/// or unbans them if the line starts with NEGATION_PREFIX.
+///
+/// Like in gitignore files, the last rule of the banlist that matches a path
+/// decides whether the path is banned.
+#[derive(Debug, Clone)]
+pub(super) struct BanRule {
+	line:    String,
+	negated: bool,
+	matcher: RuleMatcher,
+}
+impl BanRule {
+	/// Parses a banlist line, that isn't a comment or a checksum.
+	pub(super) fn parse(line: &str) -> Result<BanRule, OpenPathBanlistError> {
+		let (rule, negated) = match line.strip_prefix(NEGATION_PREFIX) {
+			Some(rule) => (rule, true),
+			None => (line, false),
+		};
+		let matcher = if rule.starts_with(GLOB_PREFIX) || rule.starts_with(REGEX_PREFIX) {
+			RuleMatcher::Pattern(BanPattern::parse(rule)?)
+		}
+		else {
+			RuleMatcher::Prefix(rule.to_string())
+		};
+		Ok(BanRule { line: line.to_string(), negated, matcher })
+	}
+
+	/// Returns the banlist line of the rule, including the NEGATION_PREFIX of negated rules.
+	pub(super) fn get_line(&self) -> &str {
+		&self.line
+	}
+
+	pub(super) fn is_negated(&self) -> bool {
+		self.negated
+	}
+
+	pub(super) fn is_pattern(&self) -> bool {
+		matches!(self.matcher, RuleMatcher::Pattern(_))
+	}
+
+	pub(super) fn is_match(&self, path: &str) -> bool {
+		match &self.matcher {
+			RuleMatcher::Prefix(prefix) => path.starts_with(prefix.as_str()),
+			RuleMatcher::Pattern(pattern) => pattern.is_match(path),
+		}
+	}
+
+	/// Returns true if the rule may match a path within the directory,
+	/// patterns are always assumed to be able to.
+	pub(super) fn may_match_within(&self, directory: &str) -> bool {
+		match &self.matcher {
+			RuleMatcher::Prefix(prefix) => prefix.starts_with(directory) || directory.starts_with(prefix.as_str()),
+			RuleMatcher::Pattern(_) => true,
+		}
+	}
+}
@@ -21,13 +21,23 @@ use super::constants::HASH_OUTPUT_LENGTH;
 
 type ChecksumArray = [u8; HASH_OUTPUT_LENGTH];
 /// Checksum defines the standard length of any checksums
-/// used in file_hasher_core.
+/// used in file_hasher_core, except for the checksums of files,
+/// which are stored as FileChecksums.
 ///
 /// Also defines a set of traits for better ergonomics.
 #[derive(Debug, Eq, PartialEq, std::hash::Hash, Copy, Clone, Default)]
 pub struct Checksum {
 	checksum: ChecksumArray,
 }
+impl Checksum {
+	/// Decodes a checksum from a hexadecimal string,
+	/// which must have the length of HASH_OUTPUT_LENGTH bytes.
+	pub fn from_hex(hex_string: &str) -> Result<Checksum, hex::FromHexError> {
+		let mut checksum = Checksum::default();
+		hex::decode_to_slice(hex_string, &mut *checksum)?;
+		Ok(checksum)
+	}
+}
 
 impl BitXorAssign<&Checksum> for Checksum {
 	fn bitxor_assign(&mut self, other: &Checksum) {
@@ -60,3 +70,58 @@ impl AsRef<ChecksumArray> for Checksum {
 		self
 	}
 }
+
+/// FileChecksum stores the checksum of a file, whose length
+/// is the output length of the hash algorithm used by the list.
+///
+/// Checksums of HASH_OUTPUT_LENGTH bytes, the length made by most
+/// algorithms, are stored inline, while checksums of other lengths
+/// are stored on the heap, such that every element of a list stays small.
+#[derive(Debug, Eq, PartialEq, std::hash::Hash, Clone)]
+pub struct FileChecksum {
+	bytes: FileChecksumBytes,
+}
+#[derive(Debug, Eq, PartialEq, std::hash::Hash, Clone)]
+enum FileChecksumBytes {
+	Inline(ChecksumArray),
+	Boxed(Box<[u8]>),
+}
+impl FileChecksum {
+	/// Creates a checksum containing the given bytes.
+	pub fn from_slice(bytes: &[u8]) -> FileChecksum {
+		let bytes = match ChecksumArray::try_from(bytes) {
+			Ok(array) => FileChecksumBytes::Inline(array),
+			Err(_) => FileChecksumBytes::Boxed(bytes.into()),
+		};
+		FileChecksum { bytes }
+	}
+
+	/// Decodes a checksum from a hexadecimal string,
+	/// the length of the checksum is given by the length of the string.
+	pub fn from_hex(hex_string: &str) -> Result<FileChecksum, hex::FromHexError> {
+		Ok(FileChecksum::from_slice(&hex::decode(hex_string)?))
+	}
+}
+
+impl std::fmt::Display for FileChecksum {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "{}", hex::encode_upper(self.as_ref()))
+	}
+}
+
+impl Deref for FileChecksum {
+	type Target = [u8];
+
+	fn deref(&self) -> &[u8] {
+		match &self.bytes {
+			FileChecksumBytes::Inline(array) => array,
+			FileChecksumBytes::Boxed(bytes) => bytes,
+		}
+	}
+}
+
+impl AsRef<[u8]> for FileChecksum {
+	fn as_ref(&self) -> &[u8] {
+		self
+	}
+}
@@ -16,10 +16,15 @@
 */
 
 pub const HASH_OUTPUT_LENGTH: usize = 32;
+pub const MAX_HASH_OUTPUT_LENGTH: usize = 64;
 pub const FIN_CHECKSUM_PREFIX: &str = "CHECKSUM = ";
 pub const XOR_CHECKSUM_PREFIX: &str = "XORCHECKSUM = ";
+pub const HASH_ALGORITHM_PREFIX: &str = "HASHALGORITHM = ";
+pub const IGNORE_FILES_CHECKSUM_PREFIX: &str = "IGNOREFILESCHECKSUM = ";
+pub const IGNORE_FILES_PREFIX: &str = "IGNOREFILES = ";
 
 pub const LIST_VERSION_PREFIX: &str = "LISTVERSION = ";
-pub const CURRENT_LIST_VERSION: &str = "1.1";
+pub const CURRENT_LIST_VERSION: &str = "1.5";
 
 pub const TMPCOPYDIR: &str = "./file_hasher_files/tmpcpyfldr/";
+pub const BACKUP_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H.%M.%S.%f %z";
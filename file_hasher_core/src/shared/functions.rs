@@ -17,9 +17,22 @@
 
 extern crate blake2;
 
+use std::{
+	collections::HashSet,
+	fs::{self, File},
+	io::{self, BufWriter, Write},
+	path::Path,
+	sync::mpsc,
+};
+
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
+
 use self::blake2::{digest::VariableOutput, Blake2bVar};
 use super::Checksum;
 
+/// The most lines shown from each side, when describing how two files differ.
+const MAX_DIFFERENCE_LINES: usize = 20;
+
 /// Converts a VarBlake2b object into an Option of a HASH_OUTPUT_LENGTH
 /// length binary array.
 ///
@@ -29,3 +42,120 @@ pub fn blake2_to_checksum(hasher: Blake2bVar) -> Checksum {
 	hasher.finalize_variable(&mut *element_hash).unwrap();
 	element_hash
 }
+
+/// Describes which lines exist in only one of the two files,
+/// such that the user can see what replacing one by the other would change.
+/// The names are used in the headers of the description.
+///
+/// Only the first MAX_DIFFERENCE_LINES lines of each side are shown.
+pub fn describe_differences(first: &str, first_name: &str, second: &str, second_name: &str) -> String {
+	let first_lines = first.lines().collect::<HashSet<_>>();
+	let second_lines = second.lines().collect::<HashSet<_>>();
+	let only_first = first.lines().filter(|line| !second_lines.contains(line)).collect::<Vec<_>>();
+	let only_second = second.lines().filter(|line| !first_lines.contains(line)).collect::<Vec<_>>();
+
+	let mut description = String::new();
+	for (lines, name) in [(only_first, first_name), (only_second, second_name)] {
+		description.push_str(&format!("Lines that are only in {} ({}):\n", name, lines.len()));
+		for line in lines.iter().take(MAX_DIFFERENCE_LINES) {
+			description.push_str(&format!("\t{}\n", line));
+		}
+		if lines.len() > MAX_DIFFERENCE_LINES {
+			description.push_str(&format!("\t... and {} more lines\n", lines.len() - MAX_DIFFERENCE_LINES));
+		}
+	}
+	description
+}
+
+/// Writes contents to the file at path, such that the file contains either
+/// its old contents or all of the new contents, even if the program crashes
+/// or the disk runs full while writing.
+///
+/// The contents are written to a temporary file in the directory of path,
+/// which is synced to disk before it is renamed over path.
+/// Afterwards the directory is synced, so that the rename is stored as well.
+pub fn write_file_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+	write_file_atomically_with(path, |writer| writer.write_all(contents))
+}
+
+/// Writes a file atomically like write_file_atomically, where the contents
+/// are written by write_contents, such that they don't need to be in memory at once.
+/// The writer given to write_contents is buffered.
+pub fn write_file_atomically_with(path: &Path, write_contents: impl FnOnce(&mut dyn Write) -> io::Result<()>) -> io::Result<()> {
+	let directory = match path.parent() {
+		Some(directory) if directory != Path::new("") => directory,
+		_ => Path::new("."),
+	};
+	let file_name = path
+		.file_name()
+		.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+	let temp_path = directory.join(format!(".{}.tmp", file_name.to_string_lossy()));
+
+	let result = File::create(&temp_path).and_then(|file| {
+		let mut writer = BufWriter::new(file);
+		write_contents(&mut writer)?;
+		writer.into_inner().map_err(|err| err.into_error())?.sync_all()
+	});
+	if let Err(err) = result.and_then(|_| fs::rename(&temp_path, path)) {
+		// The old file is untouched, only the temporary file needs to be removed.
+		let _ = fs::remove_file(&temp_path);
+		return Err(err);
+	}
+	sync_directory(directory)
+}
+
+/// Syncs the entries of a directory to disk.
+/// Directories can't be opened as files on every platform,
+/// on those platforms this does nothing.
+fn sync_directory(directory: &Path) -> io::Result<()> {
+	#[cfg(unix)]
+	{
+		File::open(directory)?.sync_all()
+	}
+	#[cfg(not(unix))]
+	{
+		let _ = directory;
+		Ok(())
+	}
+}
+
+/// Creates a thread pool with the given amount of threads.
+/// If threads is 0, a thread is created for every logical cpu.
+///
+/// Panics if the OS is unable to create the threads.
+pub fn build_thread_pool(threads: usize) -> ThreadPool {
+	ThreadPoolBuilder::new().num_threads(threads).build().expect("Unable to create thread pool")
+}
+
+/// Runs operation on every element of items, using the threads in pool.
+///
+/// The results are given to on_result on the calling thread, in the order
+/// that they are finished. This makes it possible to report progress
+/// through objects that can't be shared between threads.
+pub fn run_in_thread_pool<T: Send, R: Send>(
+	pool: &ThreadPool, items: Vec<T>, operation: impl Fn(T) -> R + Sync, on_result: impl FnMut(R),
+) {
+	run_groups_in_thread_pool(pool, items.into_iter().map(|item| vec![item]).collect(), operation, on_result);
+}
+
+/// Works like run_in_thread_pool, except that the items within
+/// a group are processed one at a time, in the order of the group.
+/// Different groups are processed in parallel.
+pub fn run_groups_in_thread_pool<T: Send, R: Send>(
+	pool: &ThreadPool, groups: Vec<Vec<T>>, operation: impl Fn(T) -> R + Sync, on_result: impl FnMut(R),
+) {
+	let (sender, receiver) = mpsc::channel();
+	std::thread::scope(|scope| {
+		scope.spawn(|| {
+			pool.install(|| {
+				groups.into_par_iter().for_each_with(sender, |sender, group| {
+					for item in group {
+						// Sending can only fail if the receiving thread has panicked.
+						let _ = sender.send(operation(item));
+					}
+				})
+			})
+		});
+		receiver.into_iter().for_each(on_result);
+	});
+}
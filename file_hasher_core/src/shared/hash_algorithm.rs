@@ -0,0 +1,121 @@
+/*
+	This file is part of file_hasher.
+
+	file_hasher is free software: you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation, either version 3 of the License, or
+	(at your option) any later version.
+
+	file_hasher is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License
+	along with file_hasher.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use blake2::{
+	digest::{Digest, Update, VariableOutput},
+	Blake2bVar,
+};
+use sha2::Sha256;
+
+use super::{constants::MAX_HASH_OUTPUT_LENGTH, FileChecksum};
+
+/// HashAlgorithm is the algorithm used for hashing the contents of files.
+///
+/// The algorithm is chosen when a list is created, and is stored
+/// in the header of the file_hashes file, so every file checksum
+/// in a list is made with the same algorithm.
+/// Lists from before the algorithm was stored use BLAKE2b-256.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HashAlgorithm {
+	#[default]
+	Blake2b256,
+	Blake2b512,
+	Sha256,
+	Blake3,
+}
+impl HashAlgorithm {
+	/// Every supported algorithm, in the order they are benchmarked.
+	pub const ALL: [HashAlgorithm; 4] =
+		[HashAlgorithm::Blake2b256, HashAlgorithm::Blake2b512, HashAlgorithm::Sha256, HashAlgorithm::Blake3];
+
+	/// Returns the identifier of the algorithm, that is stored in file_hashes.
+	pub fn identifier(&self) -> &'static str {
+		match self {
+			HashAlgorithm::Blake2b256 => "BLAKE2b-256",
+			HashAlgorithm::Blake2b512 => "BLAKE2b-512",
+			HashAlgorithm::Sha256 => "SHA-256",
+			HashAlgorithm::Blake3 => "BLAKE3",
+		}
+	}
+
+	/// Returns the length in bytes of the checksums made by the algorithm.
+	pub fn output_length(&self) -> usize {
+		match self {
+			HashAlgorithm::Blake2b512 => 64,
+			HashAlgorithm::Blake2b256 | HashAlgorithm::Sha256 | HashAlgorithm::Blake3 => 32,
+		}
+	}
+
+	/// Creates a new hasher, that hashes using this algorithm.
+	pub(crate) fn hasher(&self) -> FileHasher {
+		match self {
+			HashAlgorithm::Blake2b256 | HashAlgorithm::Blake2b512 => FileHasher::Blake2b(Blake2bVar::new(self.output_length()).unwrap()),
+			HashAlgorithm::Sha256 => FileHasher::Sha256(Sha256::new()),
+			HashAlgorithm::Blake3 => FileHasher::Blake3(Box::new(blake3::Hasher::new())),
+		}
+	}
+}
+impl std::str::FromStr for HashAlgorithm {
+	type Err = &'static str;
+
+	/// Interprets an algorithm identifier, ignoring case and dashes,
+	/// so both "SHA-256" and "sha256" are accepted.
+	fn from_str(identifier: &str) -> Result<HashAlgorithm, Self::Err> {
+		let identifier = identifier.replace('-', "");
+		HashAlgorithm::ALL
+			.into_iter()
+			.find(|algorithm| algorithm.identifier().replace('-', "").eq_ignore_ascii_case(&identifier))
+			.ok_or("Valid hash algorithms are BLAKE2b-256/BLAKE2b-512/SHA-256/BLAKE3")
+	}
+}
+impl std::fmt::Display for HashAlgorithm {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "{}", self.identifier())
+	}
+}
+
+/// FileHasher is a hasher for any of the algorithms in HashAlgorithm.
+pub(crate) enum FileHasher {
+	Blake2b(Blake2bVar),
+	Sha256(Sha256),
+	Blake3(Box<blake3::Hasher>),
+}
+impl FileHasher {
+	pub fn update(&mut self, data: &[u8]) {
+		match self {
+			FileHasher::Blake2b(hasher) => Update::update(hasher, data),
+			FileHasher::Sha256(hasher) => Update::update(hasher, data),
+			FileHasher::Blake3(hasher) => {
+				hasher.update(data);
+			},
+		}
+	}
+
+	/// Consumes the hasher, and returns the checksum of the data it was given.
+	pub fn finalize(self) -> FileChecksum {
+		match self {
+			FileHasher::Blake2b(hasher) => {
+				let mut checksum = [0; MAX_HASH_OUTPUT_LENGTH];
+				let checksum = &mut checksum[..hasher.output_size()];
+				hasher.finalize_variable(checksum).unwrap();
+				FileChecksum::from_slice(checksum)
+			},
+			FileHasher::Sha256(hasher) => FileChecksum::from_slice(&Digest::finalize(hasher)),
+			FileHasher::Blake3(hasher) => FileChecksum::from_slice(hasher.finalize().as_bytes()),
+		}
+	}
+}
@@ -32,6 +32,56 @@ pub trait UserInterface {
 	/// Gives a message, that should be shown to the user,
 	/// but the user can't reply to it.
 	fn send_message(&self, message: &str);
+
+	/// Tells the user how far a long running operation has come.
+	///
+	/// Progress is sent for every item of the operation,
+	/// user interfaces that don't show progress can ignore it.
+	fn send_progress(&self, _progress: &ProgressEvent) {}
+
+	/// Returns true if the progress sent to send_progress is shown to the user.
+	///
+	/// Long running operations then send the progress instead of a message for every item.
+	fn shows_progress(&self) -> bool {
+		false
+	}
+}
+
+/// ProgressPhase is the part of a long running operation,
+/// that a ProgressEvent belongs to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ProgressPhase {
+	Verifying,
+	Hashing,
+	HashingMoveCandidates,
+	CheckingMetadata,
+	FileOperations,
+}
+impl std::fmt::Display for ProgressPhase {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		use ProgressPhase::*;
+		match self {
+			Verifying => write!(f, "Verifying"),
+			Hashing => write!(f, "Hashing"),
+			HashingMoveCandidates => write!(f, "Hashing move candidates"),
+			CheckingMetadata => write!(f, "Checking metadata"),
+			FileOperations => write!(f, "File operations"),
+		}
+	}
+}
+
+/// ProgressEvent is sent every time an item of a long running operation is done.
+///
+/// current is the amount of items done so far, starting at 1, out of total items.
+/// bytes_processed is the amount of bytes read or copied so far in the phase,
+/// and path is the path of the item that was just done.
+#[derive(Debug, Clone)]
+pub struct ProgressEvent<'a> {
+	pub phase:           ProgressPhase,
+	pub current:         usize,
+	pub total:           usize,
+	pub bytes_processed: u64,
+	pub path:            &'a str,
 }
 pub struct StubUserInterface {
 	answer: String,
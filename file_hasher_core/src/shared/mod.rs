@@ -18,7 +18,11 @@
 mod checksum;
 pub mod constants;
 mod functions;
+mod hash_algorithm;
 mod interfacer;
-pub use checksum::Checksum;
+pub use checksum::{Checksum, FileChecksum};
 pub use functions::*;
-pub use interfacer::{AnyString, InterfacerReturnType, SlashEnding, StubUserInterface, UserInterface, YesNo, YesNoAuto};
+pub use hash_algorithm::HashAlgorithm;
+pub use interfacer::{
+	AnyString, InterfacerReturnType, ProgressEvent, ProgressPhase, SlashEnding, StubUserInterface, UserInterface, YesNo, YesNoAuto,
+};
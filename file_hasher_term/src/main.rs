@@ -14,14 +14,27 @@
 	You should have received a copy of the GNU General Public License
 	along with file_hasher.  If not, see <https://www.gnu.org/licenses/>.
 */
-use file_hasher_core::*;
+use std::{path::Path, str::FromStr};
+
+use file_hasher_core::{
+	e_d_list::errors::{ManifestLineError, VerifyError},
+	*,
+};
 
 mod term_interfacer;
+use serde_json::json;
 use structopt::StructOpt;
 
 use crate::term_interfacer::UserMessenger;
 
-fn handle_error_list(error_list: Vec<impl std::error::Error>, prepend_message: &str, no_errors_message: Option<&str>) {
+/// Exit code used when an operation could not be completed.
+const EXIT_FAILURE: i32 = 1;
+/// Exit code used when a verify operation found integrity errors.
+const EXIT_INTEGRITY_ERRORS: i32 = 2;
+const BANLIST_PATH: &str = "./file_hasher_files/banlist";
+const FILE_HASHES_PATH: &str = "./file_hasher_files/file_hashes";
+
+fn handle_error_list(error_list: &[impl std::error::Error], prepend_message: &str, no_errors_message: Option<&str>) {
 	if !error_list.is_empty() {
 		let length = error_list.len();
 		let length_width = length.to_string().chars().count();
@@ -35,90 +48,694 @@ fn handle_error_list(error_list: Vec<impl std::error::Error>, prepend_message: &
 	}
 }
 
+/// An error found by a verify operation, that can be printed as json.
+///
+/// The kind of the errors and differences printed as json is the name of their variant,
+/// these names are kept between versions, such that scripts can depend on them.
+trait JsonError: std::error::Error {
+	fn to_json(&self) -> serde_json::Value;
+}
+impl JsonError for VerifyError {
+	fn to_json(&self) -> serde_json::Value {
+		json!({ "kind": self.kind(), "path": self.path(), "message": self.to_string() })
+	}
+}
+impl JsonError for ManifestLineError {
+	fn to_json(&self) -> serde_json::Value {
+		json!({ "kind": self.kind(), "path": self.path(), "line": self.line(), "message": self.to_string() })
+	}
+}
+
+/// Prints the errors found by a verify operation in the given format.
+///
+/// Returns the exit code the program should exit with.
+fn handle_verify_errors(error_list: Vec<impl JsonError>, format: &OutputFormat) -> i32 {
+	match format {
+		OutputFormat::Text => handle_error_list(&error_list, "Errors found:", Some("No errors found!")),
+		OutputFormat::Json => {
+			let errors: Vec<_> = error_list.iter().map(JsonError::to_json).collect();
+			println!("{}", json!({ "error_count": errors.len(), "errors": errors }));
+		},
+	}
+	if error_list.is_empty() { 0 } else { EXIT_INTEGRITY_ERRORS }
+}
+
+/// Prints the differences between two lists in the given format.
+fn handle_differences(differences: &[e_d_list::ListDifference], format: &OutputFormat) {
+	match format {
+		OutputFormat::Text if differences.is_empty() => println!("The lists are identical"),
+		OutputFormat::Text => differences.iter().for_each(|difference| println!("{}", difference)),
+		OutputFormat::Json => {
+			let differences: Vec<_> = differences
+				.iter()
+				.map(|difference| {
+					json!({
+						"kind": difference.kind(),
+						"path": difference.path(),
+						"old_path": difference.old_path(),
+						"message": difference.to_string(),
+					})
+				})
+				.collect();
+			println!("{}", json!({ "difference_count": differences.len(), "differences": differences }));
+		},
+	}
+}
+
+/// Opens the list at path read-only, if no file exists at path,
+/// path is used as the name of a backup in hash_file_backups.
+fn open_list_or_backup(path: &str) -> Result<e_d_list::EDList, e_d_list::errors::EDListOpenError> {
+	if std::path::Path::new(path).exists() {
+		e_d_list::EDList::open_read_only(path)
+	}
+	else {
+		e_d_list::EDList::open_read_only(&format!("./file_hasher_files/hash_file_backups/{}", path))
+	}
+}
+
+/// Prints the differences between the list or backup old, and the list or backup new,
+/// or the current list if new is None, in the given format.
+/// Every list is opened read-only, so no backup is written.
+///
+/// Returns the exit code the program should exit with.
+fn diff_lists(old: &str, new: Option<&str>, format: &OutputFormat) -> i32 {
+	let lists = open_list_or_backup(old).and_then(|old_list| {
+		let new_list = match new {
+			Some(new) => open_list_or_backup(new)?,
+			None => e_d_list::EDList::open_read_only(FILE_HASHES_PATH)?,
+		};
+		Ok((old_list, new_list))
+	});
+	match lists {
+		Ok((old_list, new_list)) => match new_list.diff(&old_list) {
+			Ok(differences) => {
+				handle_differences(&differences, format);
+				0
+			},
+			Err(err) => {
+				eprintln!("{}", err);
+				EXIT_FAILURE
+			},
+		},
+		Err(err) => {
+			eprintln!("Error opening list, err:\n{}", err);
+			EXIT_FAILURE
+		},
+	}
+}
+
+/// Used by structopt to validate path arguments, that must end with a forward slash.
+fn parse_slash_ending(path: &str) -> Result<String, &'static str> {
+	SlashEnding::try_from(path.to_string()).map(|slash_ending| slash_ending.path)
+}
+
+/// The format used for printing the results of verify operations.
+enum OutputFormat {
+	Text,
+	Json,
+}
+impl FromStr for OutputFormat {
+	type Err = &'static str;
+
+	fn from_str(format: &str) -> Result<OutputFormat, Self::Err> {
+		match format.to_lowercase().as_str() {
+			"text" => Ok(OutputFormat::Text),
+			"json" => Ok(OutputFormat::Json),
+			_ => Err("Valid formats are text/json"),
+		}
+	}
+}
+
 #[derive(StructOpt)]
 #[structopt(name = "File Hasher", about = "A file hashing program")]
-struct Opts {}
+struct Opts {
+	/// Answer yes to all yes/no questions, instead of asking for them on stdin.
+	/// Missing banlist and file_hashes files are not created, the operation fails instead.
+	#[structopt(long)]
+	yes:            bool,
+	/// Output format of verify results, text or json.
+	/// With json, all other messages are written to stderr.
+	#[structopt(long, default_value = "text")]
+	format:         OutputFormat,
+	/// Amount of threads used for hashing files, 0 uses a thread for every cpu.
+	#[structopt(long, default_value = "1")]
+	threads:        usize,
+	/// Hash algorithm used by a new list, BLAKE2b-256, BLAKE2b-512, SHA-256 or BLAKE3.
+	/// An existing list can only be used with the algorithm it was created with.
+	#[structopt(long = "hash-algorithm")]
+	hash_algorithm: Option<HashAlgorithm>,
+	/// Format that file_hashes and its backups are written in, text or binary.
+	/// Defaults to the format file_hashes is stored in, which is detected when it is read.
+	#[structopt(long = "list-format")]
+	list_format:    Option<e_d_list::ListFormat>,
+	/// Compression that file_hashes and its backups are written in, none or gzip.
+	/// Defaults to the compression file_hashes is stored in, which is detected when it is read.
+	#[structopt(long)]
+	compression:    Option<e_d_list::ListCompression>,
+	/// The operation to run, if no operation is given, the user is asked for one.
+	#[structopt(subcommand)]
+	command:        Option<Command>,
+}
+
+#[derive(StructOpt)]
+#[structopt(rename_all = "lowercase")]
+enum Command {
+	/// Hash all files that are not yet in the list.
+	Create {
+		/// Only hash one file at a time on each filesystem device, useful for spinning disks.
+		#[structopt(long = "group-by-device")]
+		group_by_device: bool,
+	},
+	/// Verify all elements in the list.
+	Verify,
+	/// Verify the elements in the list, whose paths start with the given prefix.
+	VerifySub {
+		/// Path prefix of the elements to verify, must end with a forward slash.
+		#[structopt(long, parse(try_from_str = parse_slash_ending))]
+		prefix: String,
+	},
+	/// Verify all symbolic links in the list.
+	VerifyLinks,
+	/// Remove elements that have been deleted, modified or banned from the list.
+	Delete,
+	/// Show the elements that are excluded by the banlist or the ignore files, grouped by the rule that excludes them,
+	/// and remove all of them from the list in one confirmed step.
+	DeleteBanned,
+	/// Rehash the elements in the list whose files or links have been modified.
+	Update,
+	/// Detect elements whose files or links have been moved, and update their paths.
+	Reconcile,
+	/// Sort the list according to the paths of its elements.
+	Sort,
+	/// Show all files and links that are duplicates of each other.
+	Duplicates,
+	/// Generate a checksum of the elements under the given prefix, without the prefix.
+	RelativeChecksum {
+		/// Path prefix of the elements to include, must end with a forward slash.
+		#[structopt(long, parse(try_from_str = parse_slash_ending))]
+		prefix: String,
+	},
+	/// Synchronise a path in another indexed folder to a path in this list.
+	Sync {
+		/// Path to the other folder indexed by file_hasher, must end with a forward slash.
+		#[structopt(long, parse(try_from_str = parse_slash_ending))]
+		source: String,
+		/// Relative path in this list, that will be synced to, must end with a forward slash.
+		#[structopt(long, parse(try_from_str = parse_slash_ending))]
+		to:     String,
+		/// Relative path in the other list, that will be synced from, must end with a forward slash.
+		#[structopt(long, parse(try_from_str = parse_slash_ending))]
+		from:   String,
+	},
+	/// Export the files in the list as a manifest, that can be checked using sha256sum, b2sum or b3sum.
+	ExportManifest {
+		/// Path prefix of the files to export, must end with a forward slash.
+		#[structopt(long, default_value = "./", parse(try_from_str = parse_slash_ending))]
+		prefix: String,
+		/// Path of the manifest file to write.
+		#[structopt(long)]
+		output: String,
+	},
+	/// Add the files of a manifest that are not yet in the list to the list, using the checksums of the manifest.
+	ImportManifest {
+		/// Path of the manifest file to read.
+		#[structopt(long)]
+		manifest:  String,
+		/// Hash algorithm of the manifest, which must be the hash algorithm of the list.
+		#[structopt(long)]
+		algorithm: HashAlgorithm,
+	},
+	/// Verify the files of a manifest against the checksums of the manifest.
+	VerifyManifest {
+		/// Path of the manifest file to read.
+		#[structopt(long)]
+		manifest:  String,
+		/// Hash algorithm of the manifest, defaults to the hash algorithm of the list.
+		#[structopt(long)]
+		algorithm: Option<HashAlgorithm>,
+	},
+	/// Verify a directory against a list or a manifest stored anywhere, without writing anything.
+	/// The directory doesn't need a file_hasher_files folder, and no banlist is used.
+	VerifyReadOnly {
+		/// Path of a file_hashes list to verify against.
+		#[structopt(long, required_unless = "manifest", conflicts_with = "manifest")]
+		list:      Option<String>,
+		/// Path of a manifest to verify against.
+		#[structopt(long)]
+		manifest:  Option<String>,
+		/// Directory that the paths of the list or manifest are relative to.
+		#[structopt(long, default_value = ".")]
+		root:      String,
+		/// Hash algorithm of the manifest, defaults to BLAKE2b-256.
+		#[structopt(long, conflicts_with = "list")]
+		algorithm: Option<HashAlgorithm>,
+	},
+	/// Convert the list to the current list version, and show what was converted.
+	Migrate {
+		/// Only show what would be converted, without writing the list or a backup.
+		#[structopt(long = "dry-run")]
+		dry_run: bool,
+	},
+	/// Show the differences between an old list and the current list, or another new list.
+	/// The text and json formats of --format are supported.
+	Diff {
+		/// Path of the old list, or the name of a backup in hash_file_backups.
+		#[structopt(long)]
+		old: String,
+		/// Path of the new list, or the name of a backup in hash_file_backups, defaults to the current list.
+		#[structopt(long)]
+		new: Option<String>,
+	},
+	/// Delete the backups in hash_file_backups that the retention policy doesn't keep.
+	/// The newest backup that validates is always kept.
+	PruneBackups {
+		/// Amount of newest backups to keep.
+		#[structopt(long = "keep-last", default_value = "10")]
+		keep_last:         usize,
+		/// Amount of days to keep the newest backup of.
+		#[structopt(long = "keep-daily", default_value = "7")]
+		keep_daily:        usize,
+		/// Amount of weeks to keep the newest backup of.
+		#[structopt(long = "keep-weekly", default_value = "4")]
+		keep_weekly:       usize,
+		/// Amount of months to keep the newest backup of.
+		#[structopt(long = "keep-monthly", default_value = "12")]
+		keep_monthly:      usize,
+		/// Maximum total size in bytes of the kept backups, the oldest backups are deleted first.
+		#[structopt(long = "max-size")]
+		max_size:          Option<u64>,
+		/// Amount of newest syncbackup folders to keep, if not given no syncbackup folders are deleted.
+		#[structopt(long = "keep-sync-backups")]
+		keep_sync_backups: Option<usize>,
+	},
+	/// Benchmark the hashing performance of this computer.
+	Benchmark {
+		/// Amount of bytes to hash.
+		#[structopt(long, default_value = "10737418240")]
+		bytes: usize,
+	},
+	/// Accept the .file_hasher_ignore files after they have been added, changed or removed,
+	/// after showing which ignore files there are now. The list can't be used until they are accepted.
+	AcceptIgnoreFiles,
+	/// Show, edit or seal the banlist, without computing its checksum by hand.
+	Banlist {
+		#[structopt(subcommand)]
+		command: BanlistCommand,
+	},
+}
+
+#[derive(StructOpt)]
+#[structopt(rename_all = "lowercase")]
+enum BanlistCommand {
+	/// Show the rules of the banlist.
+	List,
+	/// Add a rule to the end of the banlist, and seal the banlist.
+	Add {
+		/// The banlist line to add, like ./build/, glob:*.tmp or !./media/originals/.
+		#[structopt(long)]
+		rule: String,
+	},
+	/// Remove a rule from the banlist, and seal the banlist.
+	Remove {
+		/// The banlist line to remove.
+		#[structopt(long)]
+		rule: String,
+	},
+	/// Verify the checksum of the banlist.
+	Verify,
+	/// Seal the banlist after it has been edited by hand, after showing how it differs from the last sealed version.
+	Reseal,
+}
+impl Command {
+	/// Asks the user for an operation, and any arguments that operation needs.
+	/// Keeps asking until a valid operation has been entered.
+	fn from_user(interfacer: &UserMessenger) -> Command {
+		loop {
+			println!("Enter one of the following operations:");
+			let answer = interfacer
+				.get_user_answer::<AnyString>(
+					"Create\nVerify\nVerifySub\nVerifyLinks\nDelete\nDeleteBanned\nUpdate\nReconcile\nSort\nDuplicates\nRelativeChecksum\
+					 \nSync\nExportManifest\nImportManifest\nVerifyManifest\nMigrate\nDiff\nPruneBackups\
+					 \nBenchmark {optional byte argument}",
+				)
+				.string
+				.to_lowercase();
+			let mut answer = answer.split(' ');
+			return match answer.next().unwrap() {
+				"create" => Command::Create { group_by_device: false },
+				"verify" => Command::Verify,
+				"verifysub" => Command::VerifySub { prefix: interfacer.get_user_answer::<SlashEnding>("Enter your path prefix").path },
+				"verifylinks" => Command::VerifyLinks,
+				"delete" => Command::Delete,
+				"deletebanned" => Command::DeleteBanned,
+				"update" => Command::Update,
+				"reconcile" => Command::Reconcile,
+				"sort" => Command::Sort,
+				"duplicates" => Command::Duplicates,
+				"relativechecksum" => {
+					Command::RelativeChecksum { prefix: interfacer.get_user_answer::<SlashEnding>("Enter the relative path:").path }
+				},
+				"sync" => Command::Sync {
+					source: interfacer.get_user_answer::<SlashEnding>("Enter path to other folder indexed by file_hasher:").path,
+					to:     interfacer
+						.get_user_answer::<SlashEnding>("Enter relative path from the current edlist, where you will sync to:")
+						.path,
+					from:   interfacer
+						.get_user_answer::<SlashEnding>("Enter relative path from the external edlist, where you will sync from")
+						.path,
+				},
+				"exportmanifest" => Command::ExportManifest {
+					prefix: interfacer
+						.get_user_answer::<SlashEnding>("Enter the path prefix of the files to export, ./ exports every file:")
+						.path,
+					output: interfacer.get_user_answer::<AnyString>("Enter the path of the manifest to write:").string,
+				},
+				"importmanifest" => {
+					let manifest = interfacer.get_user_answer::<AnyString>("Enter the path of the manifest to import:").string;
+					match interfacer.get_user_answer::<AnyString>("Enter the hash algorithm of the manifest:").string.parse() {
+						Ok(algorithm) => Command::ImportManifest { manifest, algorithm },
+						Err(err) => {
+							println!("{}", err);
+							continue;
+						},
+					}
+				},
+				"verifymanifest" => Command::VerifyManifest {
+					manifest:  interfacer.get_user_answer::<AnyString>("Enter the path of the manifest to verify:").string,
+					algorithm: None,
+				},
+				"migrate" => Command::Migrate { dry_run: false },
+				"diff" => Command::Diff {
+					old: interfacer
+						.get_user_answer::<AnyString>("Enter the path of the old list, or the name of a backup in hash_file_backups:")
+						.string,
+					new: None,
+				},
+				"prunebackups" => {
+					let policy = e_d_list::RetentionPolicy::default();
+					Command::PruneBackups {
+						keep_last:         policy.keep_last,
+						keep_daily:        policy.keep_daily,
+						keep_weekly:       policy.keep_weekly,
+						keep_monthly:      policy.keep_monthly,
+						max_size:          policy.max_total_size,
+						keep_sync_backups: policy.keep_sync_backups,
+					}
+				},
+				"benchmark" => match answer.next().map(|argument| argument.parse()).unwrap_or(Ok(1024 * 1024 * 1024 * 10)) {
+					Ok(bytes) => Command::Benchmark { bytes },
+					Err(_) => {
+						println!("Invalid byte argument entered, must be a whole positive number smaller or equal to {}", usize::MAX);
+						continue;
+					},
+				},
+				_ => {
+					println!("Invalid value entered, try again!");
+					continue;
+				},
+			};
+		}
+	}
+}
+
+/// Verifies the directory root against a list or a manifest, which are read
+/// relative to the directory file_hasher was started in.
+/// Nothing is written, so root may be on read-only media.
+///
+/// Returns the exit code the program should exit with.
+fn verify_read_only(
+	list: Option<&str>, manifest: Option<&str>, root: &str, algorithm: Option<HashAlgorithm>, opts: &Opts, interfacer: &UserMessenger,
+) -> i32 {
+	let manifest = match manifest.map(|manifest| std::env::current_dir().map(|dir| dir.join(manifest))).transpose() {
+		Ok(manifest) => manifest,
+		Err(err) => {
+			eprintln!("Error getting the current directory, err = {}", err);
+			return EXIT_FAILURE;
+		},
+	};
+	let edlist = match list.map(e_d_list::EDList::open_read_only).transpose() {
+		Ok(edlist) => edlist,
+		Err(err) => {
+			eprintln!("Error opening list, err:\n{}", err);
+			return EXIT_FAILURE;
+		},
+	};
+
+	// The paths of the list and the manifest are relative to root.
+	if let Err(err) = std::env::set_current_dir(root) {
+		eprintln!("Error changing directory to {}, err = {}", root, err);
+		return EXIT_FAILURE;
+	}
+	if let Some(edlist) = edlist {
+		return handle_verify_errors(edlist.verify(None, opts.threads, interfacer), &opts.format);
+	}
+	let manifest = manifest.expect("structopt requires either a list or a manifest");
+	match e_d_list::EDList::verify_manifest(&manifest.to_string_lossy(), algorithm.unwrap_or_default(), opts.threads, interfacer) {
+		Ok(err_list) => handle_verify_errors(err_list, &opts.format),
+		Err(err) => {
+			eprintln!("{}", err);
+			EXIT_FAILURE
+		},
+	}
+}
+
+/// Runs an operation on the banlist, which may have been edited by hand,
+/// so it is run before the banlist is opened.
+///
+/// Returns the exit code the program should exit with.
+fn run_banlist_command(command: &BanlistCommand, interfacer: &UserMessenger) -> i32 {
+	use path_banlist::{errors::BanlistEditError, PathBanlist};
+
+	let result = match command {
+		BanlistCommand::List => PathBanlist::open(interfacer)
+			.map(|banlist| banlist.get_rules().for_each(|rule| println!("{}", rule)))
+			.map_err(BanlistEditError::from),
+		BanlistCommand::Add { rule } => {
+			PathBanlist::add_rule(rule).map(|()| interfacer.send_message(&format!("Added \"{}\" to the banlist", rule)))
+		},
+		BanlistCommand::Remove { rule } => {
+			PathBanlist::remove_rule(rule).map(|()| interfacer.send_message(&format!("Removed \"{}\" from the banlist", rule)))
+		},
+		BanlistCommand::Verify => PathBanlist::open(interfacer)
+			.map(|_| interfacer.send_message("The banlist is valid"))
+			.map_err(BanlistEditError::from),
+		BanlistCommand::Reseal => PathBanlist::reseal(interfacer),
+	};
+	match result {
+		Ok(()) => 0,
+		Err(err) => {
+			eprintln!("{}", err);
+			EXIT_FAILURE
+		},
+	}
+}
+
+/// --yes must not answer whether a missing banlist or file_hashes should be created,
+/// since a missing list would then silently be replaced by an empty one,
+/// and e.g. a verify run by cron would succeed.
+///
+/// Returns true, after telling the user, if path is missing and --yes is given.
+fn missing_under_assume_yes(path: &str, opts: &Opts) -> bool {
+	let missing = opts.yes && !Path::new(path).exists();
+	if missing {
+		eprintln!("{} does not exist, run file_hasher without --yes to create it", path);
+	}
+	missing
+}
 
 fn main() {
-	let _opts = Opts::from_args();
+	let opts = Opts::from_args();
+	std::process::exit(run(opts));
+}
+
+/// Runs the operation given by opts, or asks the user for one.
+///
+/// Returns the exit code the program should exit with.
+fn run(mut opts: Opts) -> i32 {
+	let interfacer = UserMessenger::new(opts.yes, matches!(opts.format, OutputFormat::Json));
+	// The operation is chosen before anything is opened, such that every operation
+	// chosen by the user is handled in the same way as when it is given as an argument.
+	let command = opts.command.take().unwrap_or_else(|| Command::from_user(&interfacer));
 
-	let banlist = match path_banlist::PathBanlist::open(&UserMessenger::new()) {
+	// Read-only verification must not open or create anything in the current directory.
+	if let Command::VerifyReadOnly { list, manifest, root, algorithm } = &command {
+		return verify_read_only(list.as_deref(), manifest.as_deref(), root, *algorithm, &opts, &interfacer);
+	}
+	// Opening the list writes a backup, which diff and a dry run of migrate must not do.
+	if let Command::Diff { old, new } = &command {
+		return diff_lists(old, new.as_deref(), &opts.format);
+	}
+	if let Command::Migrate { dry_run: true } = &command {
+		return match e_d_list::EDList::open_read_only(FILE_HASHES_PATH) {
+			Ok(mut edlist) => {
+				interfacer.send_message(&edlist.migrate(true).to_string());
+				0
+			},
+			Err(err) => {
+				eprintln!("Error opening list, err:\n{}", err);
+				EXIT_FAILURE
+			},
+		};
+	}
+	if missing_under_assume_yes(BANLIST_PATH, &opts) {
+		return EXIT_FAILURE;
+	}
+	// The banlist can't be opened after it has been edited by hand, until it is sealed again.
+	if let Command::Banlist { command } = &command {
+		return run_banlist_command(command, &interfacer);
+	}
+
+	let banlist = match path_banlist::PathBanlist::open(&interfacer) {
 		Ok(result) => result,
 		Err(err) => {
-			println!("Error opening banlist, Error = {}", err);
-			return;
+			eprintln!("Error opening banlist, Error = {}", err);
+			return EXIT_FAILURE;
 		},
 	};
-	let mut edlist = match e_d_list::EDList::open(".", &UserMessenger::new(), banlist) {
+	if missing_under_assume_yes(FILE_HASHES_PATH, &opts) {
+		return EXIT_FAILURE;
+	}
+	let open_result = if let Command::AcceptIgnoreFiles = &command {
+		e_d_list::EDList::open_and_accept_ignore_files(".", &interfacer, banlist)
+	}
+	else {
+		e_d_list::EDList::open(".", &interfacer, banlist)
+	};
+	let mut edlist = match open_result {
 		Ok(list) => list,
 		Err(err) => {
-			println!("Error opening list, err:\n{}", err);
-			return;
+			eprintln!("Error opening list, err:\n{}", err);
+			return EXIT_FAILURE;
 		},
 	};
+	if let Some(hash_algorithm) = opts.hash_algorithm {
+		if let Err(err) = edlist.set_hash_algorithm(hash_algorithm) {
+			eprintln!("Error setting hash algorithm, {}", err);
+			return EXIT_FAILURE;
+		}
+	}
+	if let Some(list_format) = opts.list_format {
+		edlist.set_format(list_format);
+	}
+	if let Some(compression) = opts.compression {
+		edlist.set_compression(compression);
+	}
 
-
-	let interfacer = UserMessenger::new();
-
-	loop {
-		let mut break_bool = true;
-		println!("Enter one of the following operations:");
-		let answer = interfacer
-			.get_user_answer::<AnyString>(
-				"Create\nVerify\nVerifySub\nVerifyLinks\nDelete\nSort\nDuplicates\nRelativeChecksum\nSync\nBenchmark {optional byte \
-				 argument}",
-			)
-			.string
-			.to_lowercase();
-		let mut answer = answer.split(' ');
-		match answer.next().unwrap() {
-			"create" => match edlist.create(&interfacer) {
-				Ok(err_list) => {
-					handle_error_list(err_list, "There were errors during this create operation:", None);
-				},
-				Err(err) => {
-					println!("Error from edlist.create {}", err);
-					return;
-				},
+	let exit_code = match command {
+		Command::Create { group_by_device } => match edlist.create(opts.threads, group_by_device, &interfacer) {
+			Ok(err_list) => {
+				handle_error_list(&err_list, "There were errors during this create operation:", None);
+				if err_list.is_empty() { 0 } else { EXIT_FAILURE }
 			},
-			"verify" => handle_error_list(edlist.verify(None, &interfacer), "Errors found:", Some("No errors found!")),
-			"verifysub" => {
-				let prefix = interfacer.get_user_answer::<SlashEnding>("Enter your path prefix").path;
-				handle_error_list(edlist.verify(Some(&prefix), &interfacer), "Errors found:", Some("No errors found!"));
+			Err(err) => {
+				eprintln!("Error from edlist.create {}", err);
+				return EXIT_FAILURE;
 			},
-			"verifylinks" => handle_error_list(edlist.verify_links(&interfacer), "Errors found:", Some("No errors found!")),
-			"delete" => edlist.delete(&interfacer),
-			"sort" => edlist.sort(),
-			"duplicates" => edlist.find_duplicates(&interfacer),
-			"relativechecksum" => edlist.relative_checksum(&interfacer),
-			"sync" => {
-				if let Err(err) = edlist.sync(&interfacer) {
-					println!("Error during syncing: {}", err)
-				}
+		},
+		Command::Verify => handle_verify_errors(edlist.verify(None, opts.threads, &interfacer), &opts.format),
+		Command::VerifySub { prefix } => handle_verify_errors(edlist.verify(Some(&prefix), opts.threads, &interfacer), &opts.format),
+		Command::VerifyLinks => handle_verify_errors(edlist.verify_links(opts.threads, &interfacer), &opts.format),
+		Command::Delete => {
+			edlist.delete(&interfacer);
+			0
+		},
+		Command::DeleteBanned => {
+			edlist.delete_banned(&interfacer);
+			0
+		},
+		Command::Update => {
+			let err_list = edlist.update(&interfacer);
+			handle_error_list(&err_list, "There were errors during this update operation:", None);
+			if err_list.is_empty() { 0 } else { EXIT_FAILURE }
+		},
+		Command::Reconcile => match edlist.reconcile(opts.threads, &interfacer) {
+			Ok(err_list) => {
+				handle_error_list(&err_list, "There were errors during this reconcile operation:", None);
+				if err_list.is_empty() { 0 } else { EXIT_FAILURE }
 			},
-			"benchmark" => {
-				let argument = answer.next().map(|argument| argument.parse()).unwrap_or(Ok(1024 * 1024 * 1024 * 10));
-
-				match argument {
-					Ok(argument) => e_d_list::EDList::benchmark(&interfacer, argument),
-					Err(_) => {
-						println!("Invalid byte argument entered, must be a whole positive number smaller or equal to {}", usize::MAX);
-						break_bool = false;
-					},
-				}
+			Err(err) => {
+				eprintln!("Error from edlist.reconcile {}", err);
+				return EXIT_FAILURE;
 			},
-			_ => {
-				break_bool = false;
-				println!("Invalid value entered, try again!");
+		},
+		Command::Sort => {
+			edlist.sort();
+			0
+		},
+		Command::Duplicates => {
+			edlist.find_duplicates(&interfacer);
+			0
+		},
+		Command::RelativeChecksum { prefix } => {
+			edlist.relative_checksum(&prefix, &interfacer);
+			0
+		},
+		Command::Sync { source, to, from } => match edlist.sync(&source, &to, &from, &interfacer) {
+			Ok(()) => 0,
+			Err(err) => {
+				eprintln!("Error during syncing: {}", err);
+				EXIT_FAILURE
 			},
-		}
-		if break_bool {
-			break;
-		}
-	}
+		},
+		Command::ExportManifest { prefix, output } => match std::fs::write(&output, edlist.export_manifest(&prefix, &interfacer)) {
+			Ok(()) => 0,
+			Err(err) => {
+				eprintln!("Error writing manifest to {}, err = {}", output, err);
+				EXIT_FAILURE
+			},
+		},
+		Command::ImportManifest { manifest, algorithm } => match edlist.import_manifest(&manifest, algorithm, &interfacer) {
+			Ok(err_list) => {
+				handle_error_list(&err_list, "These lines of the manifest could not be imported:", None);
+				if err_list.is_empty() { 0 } else { EXIT_FAILURE }
+			},
+			Err(err) => {
+				eprintln!("{}", err);
+				return EXIT_FAILURE;
+			},
+		},
+		Command::VerifyManifest { manifest, algorithm } => {
+			let algorithm = algorithm.unwrap_or_else(|| edlist.get_hash_algorithm());
+			match e_d_list::EDList::verify_manifest(&manifest, algorithm, opts.threads, &interfacer) {
+				Ok(err_list) => handle_verify_errors(err_list, &opts.format),
+				Err(err) => {
+					eprintln!("{}", err);
+					return EXIT_FAILURE;
+				},
+			}
+		},
+		Command::VerifyReadOnly { .. } => unreachable!("VerifyReadOnly is handled before the list is opened"),
+		Command::Banlist { .. } => unreachable!("Banlist is handled before the banlist is opened"),
+		// The ignore files are accepted when the list is opened, and stored when it is written.
+		Command::AcceptIgnoreFiles => 0,
+		Command::Migrate { dry_run: true } => unreachable!("Dry runs of Migrate are handled before the list is opened"),
+		Command::Migrate { dry_run: false } => {
+			interfacer.send_message(&edlist.migrate(false).to_string());
+			0
+		},
+		Command::Diff { .. } => unreachable!("Diff is handled before the list is opened"),
+		Command::PruneBackups { keep_last, keep_daily, keep_weekly, keep_monthly, max_size, keep_sync_backups } => {
+			let policy =
+				e_d_list::RetentionPolicy { keep_last, keep_daily, keep_weekly, keep_monthly, max_total_size: max_size, keep_sync_backups };
+			match edlist.prune_backups(&policy, &interfacer) {
+				Ok(()) => 0,
+				Err(err) => {
+					eprintln!("{}", err);
+					EXIT_FAILURE
+				},
+			}
+		},
+		Command::Benchmark { bytes } => {
+			e_d_list::EDList::benchmark(&interfacer, bytes);
+			0
+		},
+	};
 
 	match edlist.write_hash_file() {
-		Ok(()) => (),
-		Err(err) => println!("Error writing EDList to file, {}", err),
+		Ok(()) => exit_code,
+		Err(err) => {
+			eprintln!("Error writing EDList to file, {}", err);
+			EXIT_FAILURE
+		},
 	}
 }
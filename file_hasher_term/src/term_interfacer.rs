@@ -15,42 +15,121 @@
 	along with file_hasher.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use std::{
+	cell::RefCell,
+	io::IsTerminal,
+	time::{Duration, Instant},
+};
+
 use file_hasher_core::*;
 
+/// The progress bar is redrawn at most once per PROGRESS_REDRAW_INTERVAL.
+const PROGRESS_REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+const PROGRESS_BAR_WIDTH: usize = 20;
+/// The progress bar is cut to PROGRESS_LINE_WIDTH characters,
+/// such that it doesn't wrap in an 80 columns wide terminal.
+const PROGRESS_LINE_WIDTH: usize = 79;
+/// Erases the current line of the terminal.
+const CLEAR_LINE: &str = "\r\x1b[2K";
+
+/// ProgressBar is the progress of the phase that is currently running.
+///
+/// The line is the last rendered progress bar,
+/// and visible is true while it is drawn on the terminal.
+struct ProgressBar {
+	phase:      ProgressPhase,
+	started:    Instant,
+	last_drawn: Instant,
+	line:       String,
+	visible:    bool,
+}
+
 /// UserMessenger is named Messenger, because it
 /// functions as an intermediary between the user and
 /// the file_hasher modules.
+///
+/// If assume_yes is true, all questions that can be
+/// answered with yes, are answered with yes without
+/// reading from stdin.
+///
+/// If use_stderr is true, all messages and questions
+/// are written to stderr instead of stdout, such that
+/// stdout can be reserved for machine readable output.
+///
+/// If stderr is a terminal, the progress of long running
+/// operations is shown as a progress bar on stderr, below the messages.
 pub struct UserMessenger {
-	stdin: std::io::Stdin,
+	stdin:         std::io::Stdin,
+	assume_yes:    bool,
+	use_stderr:    bool,
+	show_progress: bool,
+	progress_bar:  RefCell<Option<ProgressBar>>,
 }
 impl UserMessenger {
-	pub fn new() -> UserMessenger {
+	pub fn new(assume_yes: bool, use_stderr: bool) -> UserMessenger {
 		let stdin = std::io::stdin();
-		UserMessenger { stdin }
+		let show_progress = std::io::stderr().is_terminal();
+		UserMessenger { stdin, assume_yes, use_stderr, show_progress, progress_bar: RefCell::new(None) }
+	}
+
+	/// Removes the progress bar from the terminal, if it is drawn.
+	fn clear_progress(&self) {
+		if let Some(progress_bar) = self.progress_bar.borrow_mut().as_mut().filter(|progress_bar| progress_bar.visible) {
+			eprint!("{}", CLEAR_LINE);
+			progress_bar.visible = false;
+		}
+	}
+
+	/// Draws the last rendered progress bar, if a phase is running.
+	fn draw_progress(&self) {
+		if let Some(progress_bar) = self.progress_bar.borrow_mut().as_mut() {
+			eprint!("{}{}", CLEAR_LINE, progress_bar.line);
+			progress_bar.last_drawn = Instant::now();
+			progress_bar.visible = true;
+		}
+	}
+
+	/// Writes a line to either stdout or stderr,
+	/// depending on how the UserMessenger was created.
+	fn print_line(&self, line: &str) {
+		if self.use_stderr {
+			eprintln!("{}", line);
+		}
+		else {
+			println!("{}", line);
+		}
 	}
 }
 
 impl UserInterface for UserMessenger {
 	fn get_user_answer<T: InterfacerReturnType>(&self, message: &str) -> T
 	where <T as TryFrom<String>>::Error: std::fmt::Display {
+		self.clear_progress();
 		let mut input_string = String::new();
+		if self.assume_yes && T::valid_answers().is_some_and(|valid_values| valid_values.contains(&"yes")) {
+			self.print_line(&format!("{} yes", message));
+			if let Ok(res) = T::try_from("yes".to_string()) {
+				return res;
+			}
+		}
 		loop {
-			print!("{} ", message);
+			let mut print_string = format!("{} ", message);
 			if let Some(valid_values) = T::valid_answers() {
-				let mut print_string = String::new();
 				for value in valid_values {
 					print_string.push_str(value);
 					print_string.push('/');
 				}
 				print_string.pop();
-				print!("{}", print_string);
 			}
-			println!();
-			self.stdin.read_line(&mut input_string).expect("Error reading user input");
+			self.print_line(&print_string);
+			if self.stdin.read_line(&mut input_string).expect("Error reading user input") == 0 {
+				eprintln!("stdin was closed while waiting for an answer, exiting");
+				std::process::exit(1);
+			}
 
 			match T::try_from(input_string.trim_end().to_string()) {
 				Ok(res) => return res,
-				Err(err) => println!("{}", err),
+				Err(err) => self.print_line(&err.to_string()),
 			}
 
 			input_string.clear();
@@ -58,6 +137,89 @@ impl UserInterface for UserMessenger {
 	}
 
 	fn send_message(&self, message: &str) {
-		println!("{}", message);
+		self.clear_progress();
+		self.print_line(message);
+		// A cleared progress bar is drawn again by the next progress event,
+		// unless it was drawn longer than PROGRESS_REDRAW_INTERVAL ago.
+		let redraw = self
+			.progress_bar
+			.borrow()
+			.as_ref()
+			.is_some_and(|progress_bar| progress_bar.last_drawn.elapsed() >= PROGRESS_REDRAW_INTERVAL);
+		if redraw {
+			self.draw_progress();
+		}
+	}
+
+	fn send_progress(&self, progress: &ProgressEvent) {
+		if !self.show_progress {
+			return;
+		}
+		let now = Instant::now();
+		let mut progress_bar_option = self.progress_bar.borrow_mut();
+		let progress_bar = match progress_bar_option.as_mut() {
+			Some(progress_bar) if progress_bar.phase == progress.phase && progress.current > 1 => progress_bar,
+			_ => progress_bar_option.insert(ProgressBar {
+				phase:      progress.phase,
+				started:    now,
+				last_drawn: now,
+				line:       String::new(),
+				visible:    false,
+			}),
+		};
+		if progress.current >= progress.total {
+			if progress_bar.visible {
+				eprint!("{}", CLEAR_LINE);
+			}
+			*progress_bar_option = None;
+			return;
+		}
+
+		let filled = PROGRESS_BAR_WIDTH * progress.current / progress.total;
+		let elapsed = now.duration_since(progress_bar.started).as_secs_f64();
+		// Phases that don't read or copy any bytes, don't show a speed.
+		let speed = if progress.bytes_processed > 0 && elapsed > 0.0 {
+			format!(" {}/s", format_bytes(progress.bytes_processed as f64 / elapsed))
+		}
+		else {
+			String::new()
+		};
+		let line = format!(
+			"{} [{}{}] {}/{}{} {}",
+			progress.phase,
+			"#".repeat(filled),
+			"-".repeat(PROGRESS_BAR_WIDTH - filled),
+			progress.current,
+			progress.total,
+			speed,
+			progress.path
+		);
+		progress_bar.line = line.chars().take(PROGRESS_LINE_WIDTH).collect();
+		let redraw = !progress_bar.visible || now.duration_since(progress_bar.last_drawn) >= PROGRESS_REDRAW_INTERVAL;
+		drop(progress_bar_option);
+		if redraw {
+			self.draw_progress();
+		}
+	}
+
+	fn shows_progress(&self) -> bool {
+		self.show_progress
+	}
+}
+impl Drop for UserMessenger {
+	fn drop(&mut self) {
+		self.clear_progress();
+	}
+}
+
+/// Formats an amount of bytes using binary prefixes, like 1.5 MiB.
+fn format_bytes(bytes: f64) -> String {
+	const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+	let mut value = bytes;
+	let mut unit = 0;
+	while value >= 1024.0 && unit < UNITS.len() - 1 {
+		value /= 1024.0;
+		unit += 1;
 	}
+	format!("{:.1} {}", value, UNITS[unit])
 }